@@ -0,0 +1,14 @@
+-- wrk script for POST /login against a database seeded via POST /bench/seed.
+--   wrk -t4 -c32 -d30s -s bench/login.lua http://localhost:3000/login
+-- Set BENCH_STUDENTS to the number of seeded students (default 1000).
+
+local students = tonumber(os.getenv("BENCH_STUDENTS") or "1000")
+
+wrk.method = "POST"
+wrk.headers["Content-Type"] = "application/json"
+
+request = function()
+  local username = string.format("bench_%06d", math.random(1, students))
+  local body = string.format('{"username":"%s","password":"bench-password"}', username)
+  return wrk.format(nil, nil, nil, body)
+end
@@ -0,0 +1,18 @@
+-- School theme and branding assets consumed by report card, ID card and email templates.
+-- A deployment serves a single school, so the theme table holds at most one row.
+CREATE TABLE branding (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    school_name VARCHAR(255) NOT NULL,
+    primary_color VARCHAR(7) NOT NULL,
+    secondary_color VARCHAR(7) NOT NULL,
+    email_footer TEXT,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Asset bytes live in blob storage; this table records where and what they are.
+CREATE TABLE branding_assets (
+    kind VARCHAR(50) PRIMARY KEY,
+    storage_key TEXT NOT NULL,
+    content_type VARCHAR(100) NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
\ No newline at end of file
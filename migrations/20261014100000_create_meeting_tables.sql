@@ -0,0 +1,58 @@
+-- Guardians, parent-teacher meeting slots, guardian bookings and the notification outbox
+CREATE EXTENSION IF NOT EXISTS btree_gist;
+
+-- Guardians registered by the school; one row per person, however many wards they have
+CREATE TABLE guardians (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    name VARCHAR(255) NOT NULL,
+    email VARCHAR(255) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    CONSTRAINT guardians_email_key UNIQUE (email)
+);
+
+-- Only a student's registered guardians can book meetings about that ward
+CREATE TABLE student_guardians (
+    student_id UUID NOT NULL REFERENCES students(id) ON DELETE CASCADE,
+    guardian_id UUID NOT NULL REFERENCES guardians(id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (student_id, guardian_id)
+);
+
+CREATE TABLE meeting_slots (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    teacher VARCHAR(255) NOT NULL,
+    starts_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    ends_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    location VARCHAR(255),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    CHECK (ends_at > starts_at),
+    -- A teacher cannot publish two overlapping slots
+    CONSTRAINT meeting_slots_no_overlap
+        EXCLUDE USING gist (teacher WITH =, tstzrange(starts_at, ends_at) WITH &&)
+);
+
+CREATE TABLE meeting_bookings (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    slot_id UUID NOT NULL REFERENCES meeting_slots(id) ON DELETE CASCADE,
+    student_id UUID NOT NULL REFERENCES students(id) ON DELETE CASCADE,
+    guardian_id UUID NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    CONSTRAINT meeting_bookings_slot_id_key UNIQUE (slot_id),
+    -- The booking guardian must be registered for the student
+    FOREIGN KEY (student_id, guardian_id) REFERENCES student_guardians ON DELETE CASCADE
+);
+
+-- Messages waiting to be delivered; a sender marks them with sent_at
+CREATE TABLE notifications (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    recipient VARCHAR(255) NOT NULL,
+    subject TEXT NOT NULL,
+    body TEXT NOT NULL,
+    send_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    sent_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX idx_meeting_slots_teacher_starts_at ON meeting_slots(teacher, starts_at);
+CREATE INDEX idx_meeting_bookings_guardian_id ON meeting_bookings(guardian_id);
+CREATE INDEX idx_notifications_pending ON notifications(send_at) WHERE sent_at IS NULL;
\ No newline at end of file
@@ -0,0 +1,29 @@
+-- Webhook subscriptions and the outbox of deliveries waiting to be posted
+CREATE TABLE webhook_subscriptions (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    url TEXT NOT NULL,
+    -- Empty means every event type
+    event_types TEXT[] NOT NULL DEFAULT '{}',
+    -- Paths to keep from the event; NULL keeps the whole event
+    fields TEXT[],
+    -- JSON template the event is rendered into; exclusive with fields
+    template JSONB,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    CHECK (fields IS NULL OR template IS NULL)
+);
+
+CREATE TABLE webhook_deliveries (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    subscription_id UUID NOT NULL REFERENCES webhook_subscriptions(id) ON DELETE CASCADE,
+    event_type VARCHAR(100) NOT NULL,
+    payload JSONB NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    -- When the dispatcher should next try; NULL once delivered or given up on
+    next_attempt_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    last_error TEXT,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    delivered_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX idx_webhook_deliveries_pending ON webhook_deliveries(next_attempt_at) WHERE next_attempt_at IS NOT NULL;
\ No newline at end of file
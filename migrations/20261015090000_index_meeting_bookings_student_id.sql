@@ -0,0 +1,2 @@
+-- Bookings are checked for overlaps per student
+CREATE INDEX idx_meeting_bookings_student_id ON meeting_bookings(student_id);
\ No newline at end of file
@@ -0,0 +1,54 @@
+//! Guard for admin-only endpoints.
+//!
+//! Handlers take an [`Admin`] argument; the request must carry
+//! `Authorization: Bearer <ADMIN_TOKEN>` or it is rejected before the handler runs.
+//! Handlers that admins and other callers share take `Option<Admin>` instead,
+//! which is `None` without an `Authorization` header and still rejects a wrong one.
+
+use axum::{
+    extract::{FromRequestParts, OptionalFromRequestParts},
+    http::{header::AUTHORIZATION, request::Parts},
+};
+
+use crate::{ApiError, AppState};
+
+pub struct Admin;
+
+impl FromRequestParts<AppState> for Admin {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let expected = state.admin_token.as_deref().ok_or(ApiError::Unauthorized)?;
+        let provided = parts
+            .headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or(ApiError::Unauthorized)?;
+
+        if !constant_time_eq(provided.as_bytes(), expected.as_bytes()) {
+            return Err(ApiError::Unauthorized);
+        }
+        Ok(Admin)
+    }
+}
+
+impl OptionalFromRequestParts<AppState> for Admin {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Option<Self>, Self::Rejection> {
+        if !parts.headers.contains_key(AUTHORIZATION) {
+            return Ok(None);
+        }
+        <Admin as FromRequestParts<AppState>>::from_request_parts(parts, state)
+            .await
+            .map(Some)
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
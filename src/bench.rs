@@ -0,0 +1,330 @@
+//! Load-test fixtures and benchmark harness, compiled only with `--features bench`.
+//!
+//! `POST /bench/seed` fills the database with throwaway students that all share
+//! [`BENCH_PASSWORD`], so external tools such as `wrk` (see `bench/login.lua`)
+//! can hammer `/login` and `/students` directly. `POST /bench/run` drives the
+//! same handlers in-process and reports p50/p95/p99/max per path.
+//!
+//! Passwords are hashed at [`BENCH_HASH_COST`] instead of bcrypt's default, so
+//! login and enrolment timings are dominated by the queries rather than by
+//! hashing. Keep that in mind when comparing them with production numbers.
+//!
+//! Enrolment in `/bench/run` runs the same queries as `POST /students` and
+//! `POST /students/{username}/matric`, webhook fan-out included, but inside a
+//! transaction that is rolled back, so a run leaves no students, matric numbers
+//! or webhook deliveries behind. Seeded students never get matric numbers, so
+//! `DELETE /bench/seed` cannot shift the numbers handed out afterwards.
+//!
+//! Every endpoint here is admin-only. Even so, never enable this feature on a
+//! production deployment: seeding and cleanup write to `students` directly.
+
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{Query, State},
+    routing::post,
+    Json, Router,
+};
+use bcrypt::hash;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    admin::Admin, insert_student, list_students, login, set_matric_number, ApiError, AppState,
+    CreateStudentRequest, LoginRequest,
+};
+
+/// Username prefix shared by every row the harness creates.
+const BENCH_PREFIX: &str = "bench_";
+/// Plaintext password of every seeded student.
+const BENCH_PASSWORD: &str = "bench-password";
+/// Prefix of students enrolled (and rolled back) by `/bench/run`, kept apart
+/// from the seeded ones.
+const BENCH_RUN_PREFIX: &str = "bench_run_";
+/// bcrypt's minimum cost; `verify` honours whatever cost a hash was made with.
+const BENCH_HASH_COST: u32 = 4;
+
+const DEFAULT_SEED_STUDENTS: i64 = 1_000;
+const MAX_SEED_STUDENTS: i64 = 100_000;
+const DEFAULT_ITERATIONS: usize = 20;
+const MAX_ITERATIONS: usize = 1_000;
+
+#[derive(Debug, Deserialize)]
+struct SeedParams {
+    students: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+struct SeedResponse {
+    inserted: u64,
+    username_prefix: &'static str,
+    password: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+struct CleanupResponse {
+    deleted: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct RunParams {
+    iterations: Option<usize>,
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/bench/seed", post(seed).delete(cleanup))
+        .route("/bench/run", post(run))
+}
+
+fn seeded_username(index: i64) -> String {
+    format!("{BENCH_PREFIX}{index:06}")
+}
+
+async fn seed(
+    _admin: Admin,
+    State(state): State<AppState>,
+    Query(params): Query<SeedParams>,
+) -> Result<Json<SeedResponse>, ApiError> {
+    let students = params.students.unwrap_or(DEFAULT_SEED_STUDENTS);
+    if !(1..=MAX_SEED_STUDENTS).contains(&students) {
+        return Err(ApiError::BadRequest(format!(
+            "students must be between 1 and {MAX_SEED_STUDENTS}"
+        )));
+    }
+
+    // One hash for every row: seeding is not what we want to measure.
+    let hashed_password = hash(BENCH_PASSWORD, BENCH_HASH_COST)
+        .map_err(|e| ApiError::BadRequest(format!("Password hashing failed: {}", e)))?;
+    let usernames: Vec<String> = (1..=students).map(seeded_username).collect();
+
+    let inserted = sqlx::query!(
+        r#"
+        INSERT INTO students (username, password, name)
+        SELECT username, $2, username FROM UNNEST($1::TEXT[]) AS username
+        ON CONFLICT (username) DO NOTHING
+        "#,
+        &usernames,
+        hashed_password
+    )
+    .execute(&state.pool)
+    .await
+    .map_err(|_| ApiError::InternalServerError)?
+    .rows_affected();
+
+    Ok(Json(SeedResponse {
+        inserted,
+        username_prefix: BENCH_PREFIX,
+        password: BENCH_PASSWORD,
+    }))
+}
+
+async fn cleanup(
+    _admin: Admin,
+    State(state): State<AppState>,
+) -> Result<Json<CleanupResponse>, ApiError> {
+    let deleted = sqlx::query!(
+        "DELETE FROM students WHERE starts_with(username, $1)",
+        BENCH_PREFIX
+    )
+    .execute(&state.pool)
+    .await
+    .map_err(|_| ApiError::InternalServerError)?
+    .rows_affected();
+
+    Ok(Json(CleanupResponse { deleted }))
+}
+
+/// Samples collected for one benchmarked path.
+struct Timings {
+    name: &'static str,
+    samples: Vec<Duration>,
+}
+
+impl Timings {
+    fn new(name: &'static str, capacity: usize) -> Self {
+        Timings {
+            name,
+            samples: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Nearest-rank percentile of the samples, `p` in `0..=100`.
+    fn percentile(sorted: &[Duration], p: usize) -> Duration {
+        if sorted.is_empty() {
+            return Duration::ZERO;
+        }
+        let rank = (p * sorted.len()).div_ceil(100).max(1);
+        sorted[rank - 1]
+    }
+
+    fn report_line(&self) -> String {
+        let mut sorted = self.samples.clone();
+        sorted.sort();
+        format!(
+            "{:<16} n={:<6} p50={:<12} p95={:<12} p99={:<12} max={}\n",
+            self.name,
+            sorted.len(),
+            format_duration(Self::percentile(&sorted, 50)),
+            format_duration(Self::percentile(&sorted, 95)),
+            format_duration(Self::percentile(&sorted, 99)),
+            format_duration(sorted.last().copied().unwrap_or_default())
+        )
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let nanos = duration.as_nanos() as f64;
+    if nanos >= 1e9 {
+        format!("{:.4} s", nanos / 1e9)
+    } else if nanos >= 1e6 {
+        format!("{:.4} ms", nanos / 1e6)
+    } else if nanos >= 1e3 {
+        format!("{:.4} µs", nanos / 1e3)
+    } else {
+        format!("{:.4} ns", nanos)
+    }
+}
+
+async fn run(
+    _admin: Admin,
+    State(state): State<AppState>,
+    Query(params): Query<RunParams>,
+) -> Result<String, ApiError> {
+    let iterations = params.iterations.unwrap_or(DEFAULT_ITERATIONS);
+    if !(1..=MAX_ITERATIONS).contains(&iterations) {
+        return Err(ApiError::BadRequest(format!(
+            "iterations must be between 1 and {MAX_ITERATIONS}"
+        )));
+    }
+
+    let seeded = sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) FROM students
+        WHERE starts_with(username, $1) AND NOT starts_with(username, $2)
+        "#,
+        BENCH_PREFIX,
+        BENCH_RUN_PREFIX
+    )
+    .fetch_one(&state.pool)
+    .await
+    .map_err(|_| ApiError::InternalServerError)?
+    .unwrap_or(0);
+    if seeded == 0 {
+        return Err(ApiError::BadRequest(
+            "No seeded students, call POST /bench/seed first".to_string(),
+        ));
+    }
+
+    let mut login_timings = Timings::new("login", iterations);
+    let mut list_timings = Timings::new("list_students", iterations);
+    let mut enroll_timings = Timings::new("enroll", iterations);
+
+    for i in 0..iterations {
+        let payload = LoginRequest {
+            username: seeded_username(i as i64 % seeded + 1),
+            password: BENCH_PASSWORD.to_string(),
+        };
+        let start = Instant::now();
+        let _ = login(State(state.clone()), Json(payload)).await?;
+        login_timings.samples.push(start.elapsed());
+
+        let start = Instant::now();
+        let _ = list_students(State(state.clone())).await?;
+        list_timings.samples.push(start.elapsed());
+
+        // Enrolment is account creation followed by matric number assignment,
+        // timed like the handlers but never committed.
+        let username = format!("{BENCH_RUN_PREFIX}{}", Uuid::new_v4().simple());
+        let payload = CreateStudentRequest {
+            username: username.clone(),
+            password: BENCH_PASSWORD.to_string(),
+            name: username.clone(),
+        };
+        let start = Instant::now();
+        let hashed_password = hash(&payload.password, BENCH_HASH_COST)
+            .map_err(|e| ApiError::BadRequest(format!("Password hashing failed: {}", e)))?;
+        let mut tx = state
+            .pool
+            .begin()
+            .await
+            .map_err(|_| ApiError::InternalServerError)?;
+        insert_student(&mut tx, &payload, &hashed_password).await?;
+        set_matric_number(&mut tx, &username).await?;
+        tx.rollback().await.map_err(|_| ApiError::InternalServerError)?;
+        enroll_timings.samples.push(start.elapsed());
+    }
+
+    Ok([login_timings, list_timings, enroll_timings]
+        .iter()
+        .map(Timings::report_line)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `n` samples of 1ms, 2ms, ..., `n` ms, already sorted.
+    fn millis(n: u64) -> Vec<Duration> {
+        (1..=n).map(Duration::from_millis).collect()
+    }
+
+    #[test]
+    fn percentile_of_no_samples_is_zero() {
+        assert_eq!(Timings::percentile(&[], 50), Duration::ZERO);
+    }
+
+    #[test]
+    fn percentile_of_one_sample_is_that_sample() {
+        let sorted = millis(1);
+        for p in [0, 50, 95, 99, 100] {
+            assert_eq!(Timings::percentile(&sorted, p), Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn percentile_uses_nearest_rank_on_small_counts() {
+        let sorted = millis(20);
+        assert_eq!(Timings::percentile(&sorted, 0), Duration::from_millis(1));
+        assert_eq!(Timings::percentile(&sorted, 50), Duration::from_millis(10));
+        assert_eq!(Timings::percentile(&sorted, 95), Duration::from_millis(19));
+        // 99% of 20 is 19.8, which rounds up to the slowest sample.
+        assert_eq!(Timings::percentile(&sorted, 99), Duration::from_millis(20));
+        assert_eq!(Timings::percentile(&sorted, 100), Duration::from_millis(20));
+    }
+
+    #[test]
+    fn percentile_matches_rank_on_a_hundred_samples() {
+        let sorted = millis(100);
+        assert_eq!(Timings::percentile(&sorted, 1), Duration::from_millis(1));
+        assert_eq!(Timings::percentile(&sorted, 50), Duration::from_millis(50));
+        assert_eq!(Timings::percentile(&sorted, 95), Duration::from_millis(95));
+        assert_eq!(Timings::percentile(&sorted, 99), Duration::from_millis(99));
+        assert_eq!(Timings::percentile(&sorted, 100), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn format_duration_picks_the_largest_fitting_unit() {
+        assert_eq!(format_duration(Duration::ZERO), "0.0000 ns");
+        assert_eq!(format_duration(Duration::from_nanos(999)), "999.0000 ns");
+        assert_eq!(format_duration(Duration::from_nanos(1_500)), "1.5000 µs");
+        assert_eq!(format_duration(Duration::from_micros(2_500)), "2.5000 ms");
+        assert_eq!(format_duration(Duration::from_secs(1)), "1.0000 s");
+    }
+
+    #[test]
+    fn report_line_sorts_samples_before_ranking() {
+        let mut timings = Timings::new("login", 3);
+        timings.samples = vec![
+            Duration::from_millis(3),
+            Duration::from_millis(1),
+            Duration::from_millis(2),
+        ];
+        let line = timings.report_line();
+        assert!(line.starts_with("login"));
+        assert!(line.contains("n=3 "));
+        assert!(line.contains("p50=2.0000 ms"));
+        assert!(line.contains("max=3.0000 ms"));
+    }
+}
@@ -0,0 +1,322 @@
+//! School theme values (name, colours, email footer) and branding assets
+//! (logo, signatures, stamp) used when rendering report cards, ID cards and emails.
+//!
+//! Admins set the theme with `PUT /branding` and upload assets as raw request
+//! bodies with `PUT /branding/assets/{kind}`. Asset bytes go through
+//! [`Storage`](crate::storage::Storage); the database only records their key
+//! and content type. Renderers should read everything through [`Branding::load`]
+//! and [`asset_bytes`] rather than querying the tables themselves.
+
+use axum::{
+    body::Bytes,
+    extract::{Path, State},
+    http::{
+        header::{CONTENT_SECURITY_POLICY, CONTENT_TYPE, X_CONTENT_TYPE_OPTIONS},
+        HeaderMap,
+    },
+    response::{IntoResponse, Json},
+    routing::get,
+    Router,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+
+use uuid::Uuid;
+
+use crate::{admin::Admin, ApiError, AppState};
+
+const ASSET_KINDS: &[&str] = &["logo", "principal_signature", "registrar_signature", "stamp"];
+// SVG is left out on purpose: it can carry script and assets are served from
+// the API's own origin.
+const ASSET_CONTENT_TYPES: &[&str] = &["image/png", "image/jpeg"];
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ThemeRequest {
+    school_name: String,
+    primary_color: String,
+    secondary_color: String,
+    email_footer: Option<String>,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct Theme {
+    pub school_name: String,
+    pub primary_color: String,
+    pub secondary_color: String,
+    pub email_footer: Option<String>,
+}
+
+#[derive(Debug, FromRow)]
+struct BrandingAssetRow {
+    kind: String,
+    storage_key: String,
+    content_type: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BrandingAsset {
+    pub kind: String,
+    pub content_type: String,
+    pub url: String,
+}
+
+impl BrandingAssetRow {
+    fn to_response(&self) -> BrandingAsset {
+        BrandingAsset {
+            kind: self.kind.clone(),
+            content_type: self.content_type.clone(),
+            url: format!("/branding/assets/{}", self.kind),
+        }
+    }
+}
+
+/// Everything a template needs to brand its output. `theme` is `None` until an
+/// admin has configured one.
+#[derive(Debug, Serialize)]
+pub struct Branding {
+    pub theme: Option<Theme>,
+    pub assets: Vec<BrandingAsset>,
+}
+
+impl Branding {
+    pub async fn load(pool: &PgPool) -> Result<Self, ApiError> {
+        let theme = sqlx::query_as!(
+            Theme,
+            "SELECT school_name, primary_color, secondary_color, email_footer FROM branding"
+        )
+        .fetch_optional(pool)
+        .await
+        .map_err(|_| ApiError::InternalServerError)?;
+
+        let assets = sqlx::query_as!(
+            BrandingAssetRow,
+            "SELECT kind, storage_key, content_type FROM branding_assets ORDER BY kind"
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(|_| ApiError::InternalServerError)?;
+
+        Ok(Branding {
+            theme,
+            assets: assets.iter().map(BrandingAssetRow::to_response).collect(),
+        })
+    }
+}
+
+/// Returns the content type and bytes of an uploaded asset, or `None` if no
+/// asset of that kind has been uploaded.
+pub async fn asset_bytes(state: &AppState, kind: &str) -> Result<Option<(String, Vec<u8>)>, ApiError> {
+    let Some(asset) = sqlx::query_as!(
+        BrandingAssetRow,
+        "SELECT kind, storage_key, content_type FROM branding_assets WHERE kind = $1",
+        kind
+    )
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(|_| ApiError::InternalServerError)?
+    else {
+        return Ok(None);
+    };
+
+    let bytes = state
+        .storage
+        .get(&asset.storage_key)
+        .await
+        .map_err(|_| ApiError::InternalServerError)?;
+    Ok(bytes.map(|bytes| (asset.content_type, bytes)))
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/branding", get(get_branding).put(update_theme))
+        .route("/branding/assets/{kind}", get(get_asset).put(upload_asset))
+}
+
+fn is_hex_color(value: &str) -> bool {
+    value.len() == 7
+        && value.starts_with('#')
+        && value[1..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn validate_asset_kind(kind: &str) -> Result<(), ApiError> {
+    if ASSET_KINDS.contains(&kind) {
+        Ok(())
+    } else {
+        Err(ApiError::BadRequest(format!(
+            "Unknown asset kind '{kind}', expected one of: {}",
+            ASSET_KINDS.join(", ")
+        )))
+    }
+}
+
+async fn get_branding(State(state): State<AppState>) -> Result<Json<Branding>, ApiError> {
+    Ok(Json(Branding::load(&state.pool).await?))
+}
+
+async fn update_theme(
+    _admin: Admin,
+    State(state): State<AppState>,
+    Json(payload): Json<ThemeRequest>,
+) -> Result<Json<Theme>, ApiError> {
+    if payload.school_name.trim().is_empty() {
+        return Err(ApiError::BadRequest("school_name must not be empty".to_string()));
+    }
+    for (field, value) in [
+        ("primary_color", &payload.primary_color),
+        ("secondary_color", &payload.secondary_color),
+    ] {
+        if !is_hex_color(value) {
+            return Err(ApiError::BadRequest(format!(
+                "{field} must be a hex colour like #1A2B3C"
+            )));
+        }
+    }
+
+    let theme = sqlx::query_as!(
+        Theme,
+        r#"
+        INSERT INTO branding (school_name, primary_color, secondary_color, email_footer)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (id) DO UPDATE
+        SET school_name = EXCLUDED.school_name,
+            primary_color = EXCLUDED.primary_color,
+            secondary_color = EXCLUDED.secondary_color,
+            email_footer = EXCLUDED.email_footer,
+            updated_at = NOW()
+        RETURNING school_name, primary_color, secondary_color, email_footer
+        "#,
+        payload.school_name.trim(),
+        payload.primary_color,
+        payload.secondary_color,
+        payload.email_footer
+    )
+    .fetch_one(&state.pool)
+    .await
+    .map_err(|_| ApiError::InternalServerError)?;
+
+    Ok(Json(theme))
+}
+
+async fn upload_asset(
+    _admin: Admin,
+    State(state): State<AppState>,
+    Path(kind): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<BrandingAsset>, ApiError> {
+    validate_asset_kind(&kind)?;
+
+    let content_type = headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .map(|value| value.trim().to_ascii_lowercase())
+        .filter(|value| ASSET_CONTENT_TYPES.contains(&value.as_str()))
+        .ok_or_else(|| {
+            ApiError::BadRequest(format!(
+                "Content-Type must be one of: {}",
+                ASSET_CONTENT_TYPES.join(", ")
+            ))
+        })?;
+    if body.is_empty() {
+        return Err(ApiError::BadRequest("Asset body must not be empty".to_string()));
+    }
+
+    let previous_key = sqlx::query_scalar!(
+        "SELECT storage_key FROM branding_assets WHERE kind = $1",
+        kind
+    )
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(|_| ApiError::InternalServerError)?;
+
+    // Every upload gets its own key so the recorded asset keeps pointing at
+    // intact bytes until the new row is in place.
+    let storage_key = format!("branding/{kind}-{}", Uuid::new_v4().simple());
+    state
+        .storage
+        .put(&storage_key, &body)
+        .await
+        .map_err(|_| ApiError::InternalServerError)?;
+
+    let asset = sqlx::query_as!(
+        BrandingAssetRow,
+        r#"
+        INSERT INTO branding_assets (kind, storage_key, content_type)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (kind) DO UPDATE
+        SET storage_key = EXCLUDED.storage_key,
+            content_type = EXCLUDED.content_type,
+            updated_at = NOW()
+        RETURNING kind, storage_key, content_type
+        "#,
+        kind,
+        storage_key,
+        content_type
+    )
+    .fetch_one(&state.pool)
+    .await;
+
+    let asset = match asset {
+        Ok(asset) => asset,
+        Err(_) => {
+            let _ = state.storage.delete(&storage_key).await;
+            return Err(ApiError::InternalServerError);
+        }
+    };
+    // The old bytes are unreachable now; failing to remove them only leaks disk.
+    if let Some(previous_key) = previous_key {
+        let _ = state.storage.delete(&previous_key).await;
+    }
+
+    Ok(Json(asset.to_response()))
+}
+
+async fn get_asset(
+    State(state): State<AppState>,
+    Path(kind): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    validate_asset_kind(&kind)?;
+    let (content_type, bytes) = asset_bytes(&state, &kind)
+        .await?
+        .ok_or(ApiError::NotFound("Branding asset"))?;
+
+    Ok((
+        [
+            (CONTENT_TYPE, content_type),
+            (X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+            (CONTENT_SECURITY_POLICY, "sandbox; default-src 'none'".to_string()),
+        ],
+        bytes,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_colors_need_a_hash_and_six_digits() {
+        assert!(is_hex_color("#1A2B3C"));
+        assert!(is_hex_color("#abcdef"));
+        assert!(!is_hex_color("1A2B3C"));
+        assert!(!is_hex_color("#1A2B3"));
+        assert!(!is_hex_color("#1A2B3C4"));
+        assert!(!is_hex_color("#GGGGGG"));
+        assert!(!is_hex_color("#fff"));
+        assert!(!is_hex_color(""));
+        // Multi-byte input must not panic on the slice after `#`.
+        assert!(!is_hex_color("#ééé"));
+    }
+
+    #[test]
+    fn only_known_asset_kinds_are_accepted() {
+        for kind in ASSET_KINDS {
+            assert!(validate_asset_kind(kind).is_ok());
+        }
+        assert!(validate_asset_kind("Logo").is_err());
+        assert!(validate_asset_kind("favicon").is_err());
+        assert!(validate_asset_kind("../logo").is_err());
+        assert!(validate_asset_kind("").is_err());
+    }
+}
@@ -1,5 +1,7 @@
 pub struct Config {
     pub db_url: String,
+    pub storage_dir: String,
+    pub admin_token: Option<String>,
 }
 
 impl Config {
@@ -10,6 +12,10 @@ impl Config {
 
         Config {
             db_url: std::env::var("DATABASE_URL").expect("DATABASE_URL must be set"),
+            storage_dir: std::env::var("STORAGE_DIR").unwrap_or_else(|_| "storage".to_string()),
+            // Admin-only endpoints reject every request while this is unset or
+            // empty, so the blank `ADMIN_TOKEN=` in .env.example locks them.
+            admin_token: std::env::var("ADMIN_TOKEN").ok().filter(|t| !t.is_empty()),
         }
     }
 }
\ No newline at end of file
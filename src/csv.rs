@@ -0,0 +1,84 @@
+//! Minimal CSV encoding for export endpoints.
+
+use axum::{
+    http::header::{CONTENT_DISPOSITION, CONTENT_TYPE},
+    response::{IntoResponse, Response},
+};
+
+/// Quotes fields that need it, and defuses values a spreadsheet would run as a
+/// formula by prefixing them with `'`.
+fn escape(field: &str) -> String {
+    let field = if field.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{field}")
+    } else {
+        field.to_string()
+    };
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field
+    }
+}
+
+/// Encodes a header row followed by `rows`, using CRLF line endings (RFC 4180).
+pub fn encode<I, R, S>(header: &[&str], rows: I) -> String
+where
+    I: IntoIterator<Item = R>,
+    R: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut out = header.iter().map(|h| escape(h)).collect::<Vec<_>>().join(",");
+    out.push_str("\r\n");
+    for row in rows {
+        let fields: Vec<String> = row.into_iter().map(|f| escape(f.as_ref())).collect();
+        out.push_str(&fields.join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+/// Wraps encoded CSV in a response that browsers download as `filename`.
+pub fn attachment(filename: &str, body: String) -> Response {
+    (
+        [
+            (CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (CONTENT_DISPOSITION, format!("attachment; filename=\"{filename}\"")),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_fields_with_separators() {
+        assert_eq!(escape("plain"), "plain");
+        assert_eq!(escape("a,b"), "\"a,b\"");
+        assert_eq!(escape("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(escape("two\nlines"), "\"two\nlines\"");
+    }
+
+    #[test]
+    fn defuses_formula_prefixes() {
+        assert_eq!(escape("=cmd|x"), "'=cmd|x");
+        assert_eq!(escape("+1"), "'+1");
+        assert_eq!(escape("-1"), "'-1");
+        assert_eq!(escape("@SUM(A1)"), "'@SUM(A1)");
+        assert_eq!(escape("\tx"), "'\tx");
+        assert_eq!(escape("\rx"), "\"'\rx\"");
+        assert_eq!(
+            escape("=HYPERLINK(\"http://x\",\"y\")"),
+            "\"'=HYPERLINK(\"\"http://x\"\",\"\"y\"\")\""
+        );
+        assert_eq!(escape("a=b"), "a=b");
+    }
+
+    #[test]
+    fn encodes_header_and_rows() {
+        let body = encode(&["name", "email"], [["Amy", "=x@y.z"], ["Bob", "b@y.z"]]);
+        assert_eq!(body, "name,email\r\nAmy,'=x@y.z\r\nBob,b@y.z\r\n");
+    }
+}
@@ -0,0 +1,137 @@
+//! Guardians the school has registered for a student.
+//!
+//! Only admins manage the list. A guardian is one row per email address, linked
+//! to each of their wards, so a parent registered for two siblings is the same
+//! guardian for both. Features that act on a guardian's behalf, such as meeting
+//! bookings, look the guardian up here instead of trusting a name and email
+//! typed into the request.
+
+use axum::{
+    extract::{Path, State},
+    response::Json,
+    routing::get,
+    Router,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::{admin::Admin, ApiError, AppState};
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CreateGuardianRequest {
+    name: String,
+    email: String,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct Guardian {
+    pub id: Uuid,
+    pub name: String,
+    pub email: String,
+}
+
+/// Emails are stored and compared lowercased.
+pub fn normalize_email(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new().route(
+        "/students/{matric_number}/guardians",
+        get(list_guardians).post(create_guardian),
+    )
+}
+
+async fn student_id(state: &AppState, matric_number: &str) -> Result<Uuid, ApiError> {
+    sqlx::query_scalar!(
+        "SELECT id FROM students WHERE matric_number = $1",
+        matric_number
+    )
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(|_| ApiError::InternalServerError)?
+    .ok_or(ApiError::NotFound("Student"))
+}
+
+async fn create_guardian(
+    _admin: Admin,
+    State(state): State<AppState>,
+    Path(matric_number): Path<String>,
+    Json(payload): Json<CreateGuardianRequest>,
+) -> Result<Json<Guardian>, ApiError> {
+    let name = payload.name.trim();
+    let email = normalize_email(&payload.email);
+    if name.is_empty() {
+        return Err(ApiError::BadRequest("name must not be empty".to_string()));
+    }
+    if !email.contains('@') {
+        return Err(ApiError::BadRequest("email is not a valid email address".to_string()));
+    }
+
+    let student_id = student_id(&state, &matric_number).await?;
+    let mut tx = state
+        .pool
+        .begin()
+        .await
+        .map_err(|_| ApiError::InternalServerError)?;
+
+    // Registering a known email again links the existing guardian, taking the
+    // name given this time.
+    let guardian = sqlx::query_as!(
+        Guardian,
+        r#"
+        INSERT INTO guardians (name, email)
+        VALUES ($1, $2)
+        ON CONFLICT (email) DO UPDATE SET name = EXCLUDED.name
+        RETURNING id, name, email
+        "#,
+        name,
+        email
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|_| ApiError::InternalServerError)?;
+
+    sqlx::query!(
+        "INSERT INTO student_guardians (student_id, guardian_id) VALUES ($1, $2)",
+        student_id,
+        guardian.id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(err) if err.constraint() == Some("student_guardians_pkey") => {
+            ApiError::Conflict("Guardian is already registered for this student")
+        }
+        _ => ApiError::InternalServerError,
+    })?;
+
+    tx.commit().await.map_err(|_| ApiError::InternalServerError)?;
+
+    Ok(Json(guardian))
+}
+
+async fn list_guardians(
+    _admin: Admin,
+    State(state): State<AppState>,
+    Path(matric_number): Path<String>,
+) -> Result<Json<Vec<Guardian>>, ApiError> {
+    let student_id = student_id(&state, &matric_number).await?;
+    let guardians = sqlx::query_as!(
+        Guardian,
+        r#"
+        SELECT g.id, g.name, g.email
+        FROM guardians g
+        JOIN student_guardians sg ON sg.guardian_id = g.id
+        WHERE sg.student_id = $1
+        ORDER BY sg.created_at
+        "#,
+        student_id
+    )
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|_| ApiError::InternalServerError)?;
+
+    Ok(Json(guardians))
+}
@@ -0,0 +1,211 @@
+//! Minimal HTTP/1.1 client for posting JSON to webhook subscribers.
+//!
+//! Each request opens a fresh connection (TLS via `native-tls` for `https://`),
+//! sends one `POST` with `Connection: close` and reads back only the status
+//! line. The blocking socket work runs on Tokio's blocking pool, bounded by
+//! [`TIMEOUT`] per connect, read and write.
+
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::TcpStream,
+    time::Duration,
+};
+
+use native_tls::TlsConnector;
+use thiserror::Error;
+use url::{Position, Url};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+const USER_AGENT: &str = "studentApi-webhooks/1.0";
+
+#[derive(Debug, Error)]
+pub enum HttpError {
+    #[error("invalid URL: {0}")]
+    InvalidUrl(String),
+    #[error("connection failed: {0}")]
+    Io(#[from] io::Error),
+    #[error("TLS failed: {0}")]
+    Tls(String),
+    #[error("invalid header: {0}")]
+    InvalidHeader(String),
+    #[error("invalid response: {0}")]
+    InvalidResponse(String),
+}
+
+/// Posts `body` as JSON to `url` with the extra `headers` and returns the
+/// response status code, whatever it is.
+pub async fn post_json(
+    url: &str,
+    headers: Vec<(&'static str, String)>,
+    body: Vec<u8>,
+) -> Result<u16, HttpError> {
+    let url = Url::parse(url).map_err(|e| HttpError::InvalidUrl(e.to_string()))?;
+    tokio::task::spawn_blocking(move || post_blocking(&url, &headers, &body))
+        .await
+        .map_err(|e| HttpError::Io(io::Error::other(e)))?
+}
+
+fn post_blocking(url: &Url, headers: &[(&str, String)], body: &[u8]) -> Result<u16, HttpError> {
+    let tls = match url.scheme() {
+        "http" => false,
+        "https" => true,
+        scheme => return Err(HttpError::InvalidUrl(format!("unsupported scheme {scheme}"))),
+    };
+    let host = url
+        .host_str()
+        .ok_or_else(|| HttpError::InvalidUrl("missing host".to_string()))?;
+    let addr = url
+        .socket_addrs(|| None)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| HttpError::InvalidUrl(format!("{host} did not resolve")))?;
+
+    let stream = TcpStream::connect_timeout(&addr, TIMEOUT)?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+
+    let head = request_head(url, headers, body.len())?;
+    if !tls {
+        return exchange(stream, &head, body);
+    }
+    let connector = TlsConnector::new().map_err(|e| HttpError::Tls(e.to_string()))?;
+    let stream = connector
+        .connect(host, stream)
+        .map_err(|e| HttpError::Tls(e.to_string()))?;
+    exchange(stream, &head, body)
+}
+
+/// Request line and headers, up to and including the blank line.
+fn request_head(url: &Url, headers: &[(&str, String)], content_length: usize) -> Result<String, HttpError> {
+    let host = match (url.host_str(), url.port()) {
+        (Some(host), Some(port)) => format!("{host}:{port}"),
+        (Some(host), None) => host.to_string(),
+        (None, _) => return Err(HttpError::InvalidUrl("missing host".to_string())),
+    };
+    let target = &url[Position::BeforePath..Position::AfterQuery];
+
+    let mut head = format!(
+        "POST {target} HTTP/1.1\r\n\
+         Host: {host}\r\n\
+         User-Agent: {USER_AGENT}\r\n\
+         Content-Type: application/json\r\n\
+         Content-Length: {content_length}\r\n\
+         Connection: close\r\n"
+    );
+    for (name, value) in headers {
+        // Values are built by us, but a stray CR/LF would split the request.
+        if value.contains(['\r', '\n']) {
+            return Err(HttpError::InvalidHeader(format!("{name} contains a line break")));
+        }
+        head.push_str(&format!("{name}: {value}\r\n"));
+    }
+    head.push_str("\r\n");
+    Ok(head)
+}
+
+fn exchange(mut stream: impl Read + Write, head: &str, body: &[u8]) -> Result<u16, HttpError> {
+    stream.write_all(head.as_bytes())?;
+    stream.write_all(body)?;
+    stream.flush()?;
+
+    let mut status_line = String::new();
+    BufReader::new(stream).read_line(&mut status_line)?;
+    parse_status_line(&status_line)
+}
+
+/// The status code from a line like `HTTP/1.1 204 No Content`.
+fn parse_status_line(line: &str) -> Result<u16, HttpError> {
+    let mut parts = line.trim_end().splitn(3, ' ');
+    match (parts.next(), parts.next()) {
+        (Some(version), Some(code)) if version.starts_with("HTTP/") && code.len() == 3 => code
+            .parse()
+            .map_err(|_| HttpError::InvalidResponse(line.trim_end().to_string())),
+        _ => Err(HttpError::InvalidResponse(line.trim_end().to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::TcpListener, thread};
+
+    use super::*;
+
+    #[test]
+    fn parses_status_lines() {
+        assert_eq!(parse_status_line("HTTP/1.1 204 No Content\r\n").unwrap(), 204);
+        assert_eq!(parse_status_line("HTTP/1.0 500\r\n").unwrap(), 500);
+        assert!(parse_status_line("").is_err());
+        assert!(parse_status_line("SMTP/1.1 200 OK").is_err());
+        assert!(parse_status_line("HTTP/1.1 2000 OK").is_err());
+        assert!(parse_status_line("HTTP/1.1 abc OK").is_err());
+    }
+
+    #[test]
+    fn request_head_carries_target_host_and_headers() {
+        let url = Url::parse("https://hooks.example.com:8443/in/abc?token=1").unwrap();
+        let head = request_head(&url, &[("X-Webhook-Event", "student.created".to_string())], 12).unwrap();
+        assert!(head.starts_with("POST /in/abc?token=1 HTTP/1.1\r\n"));
+        assert!(head.contains("\r\nHost: hooks.example.com:8443\r\n"));
+        assert!(head.contains("\r\nContent-Length: 12\r\n"));
+        assert!(head.contains("\r\nX-Webhook-Event: student.created\r\n"));
+        assert!(head.ends_with("\r\n\r\n"));
+
+        // Default ports stay out of the Host header, and an empty path is `/`.
+        let url = Url::parse("http://hooks.example.com").unwrap();
+        let head = request_head(&url, &[], 0).unwrap();
+        assert!(head.starts_with("POST / HTTP/1.1\r\nHost: hooks.example.com\r\n"));
+    }
+
+    #[test]
+    fn request_head_rejects_line_breaks_in_header_values() {
+        let url = Url::parse("http://hooks.example.com/").unwrap();
+        assert!(request_head(&url, &[("X-Test", "a\r\nHost: evil".to_string())], 0).is_err());
+    }
+
+    #[tokio::test]
+    async fn posts_body_and_returns_status() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request = String::new();
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some(value) = line.strip_prefix("Content-Length: ") {
+                    content_length = value.trim().parse().unwrap();
+                }
+                request.push_str(&line);
+                if line == "\r\n" {
+                    break;
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+            stream.write_all(b"HTTP/1.1 202 Accepted\r\nContent-Length: 0\r\n\r\n").unwrap();
+            (request, body)
+        });
+
+        let status = post_json(
+            &format!("http://127.0.0.1:{port}/hook"),
+            vec![("X-Webhook-Event", "meeting.booked".to_string())],
+            br#"{"ok":true}"#.to_vec(),
+        )
+        .await
+        .unwrap();
+        let (request, body) = server.join().unwrap();
+
+        assert_eq!(status, 202);
+        assert!(request.starts_with("POST /hook HTTP/1.1\r\n"));
+        assert!(request.contains("X-Webhook-Event: meeting.booked\r\n"));
+        assert_eq!(body, br#"{"ok":true}"#);
+    }
+
+    #[tokio::test]
+    async fn rejects_unsupported_schemes() {
+        let result = post_json("ftp://127.0.0.1:21/", vec![], vec![]).await;
+        assert!(matches!(result, Err(HttpError::InvalidUrl(_))));
+    }
+}
@@ -10,16 +10,28 @@ use sqlx::{postgres::PgPoolOptions, PgPool, FromRow};
 use thiserror::Error;
 use uuid::Uuid;
 use bcrypt::{hash, verify, DEFAULT_COST};
+mod admin;
+mod branding;
 mod config; 
+mod csv;
+mod guardians;
+mod http;
+mod meetings;
+mod notifications;
+mod reports;
+mod storage;
+mod webhooks;
+#[cfg(feature = "bench")]
+mod bench;
 
 #[derive(Debug, Error)]
 enum ApiError {
-    #[error("Student not found")]
-    NotFound,
+    #[error("{0} not found")]
+    NotFound(&'static str),
     #[error("Invalid credentials")]
     Unauthorized,
-    #[error("Username already exists")]
-    Conflict,
+    #[error("{0}")]
+    Conflict(&'static str),
     #[error("Bad request: {0}")]
     BadRequest(String),
     #[error("Internal server error")]
@@ -29,9 +41,9 @@ enum ApiError {
 impl IntoResponse for ApiError {
     fn into_response(self) -> axum::response::Response {
         let status = match self {
-            ApiError::NotFound => StatusCode::NOT_FOUND,
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
             ApiError::Unauthorized => StatusCode::UNAUTHORIZED,
-            ApiError::Conflict => StatusCode::CONFLICT,
+            ApiError::Conflict(_) => StatusCode::CONFLICT,
             ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
             ApiError::InternalServerError => StatusCode::INTERNAL_SERVER_ERROR,
         };
@@ -61,6 +73,7 @@ struct LoginRequest {
 
 #[derive(Debug, FromRow)]
 struct Student {
+    #[allow(dead_code)]
     id: Uuid,
     username: String,
     password: String,
@@ -80,16 +93,30 @@ impl Student {
 
 #[derive(Debug, Clone)]
 struct AppState {
-    pool: PgPool
+    pool: PgPool,
+    storage: storage::Storage,
+    admin_token: Option<String>
 }
 
-async fn create_student(
-    State(state): State<AppState>, 
-    Json(payload): Json<CreateStudentRequest>) 
-    -> Result<Json<StudentResponse>, ApiError> {
-    let hashed_password = hash(&payload.password, DEFAULT_COST)
-        .map_err(|e| ApiError::BadRequest(format!("Password hashing failed: {}", e)))?;
+/// Queues a webhook event for `data`, for handlers that already hold a transaction.
+async fn emit_event(
+    conn: &mut sqlx::PgConnection,
+    event_type: &str,
+    data: &impl Serialize,
+) -> Result<(), ApiError> {
+    let data = serde_json::to_value(data).map_err(|_| ApiError::InternalServerError)?;
+    webhooks::emit(conn, event_type, data)
+        .await
+        .map_err(|_| ApiError::InternalServerError)
+}
 
+/// Creates the student and queues its webhook event on `conn`, leaving the
+/// commit to the caller.
+async fn insert_student(
+    conn: &mut sqlx::PgConnection,
+    payload: &CreateStudentRequest,
+    hashed_password: &str,
+) -> Result<StudentResponse, ApiError> {
     let student = sqlx::query_as!(
         Student,
         r#"
@@ -101,25 +128,29 @@ async fn create_student(
         hashed_password,
         payload.name
     )
-    .fetch_one(&state.pool)
+    .fetch_one(&mut *conn)
     .await
     .map_err(|e| match e {
         sqlx::Error::Database(err) if err.constraint() == Some("students_username_key") => {
-            ApiError::Conflict
+            ApiError::Conflict("Username already exists")
         }
         _ => ApiError::InternalServerError
     })?;
 
-    Ok(Json(student.to_response()))
+    let response = student.to_response();
+    emit_event(conn, webhooks::STUDENT_CREATED, &response).await?;
+    Ok(response)
 }
 
-async fn assign_matric_number(
-    State(state): State<AppState>,
-    Path(username): Path<String> 
-) -> Result<Json<StudentResponse>, ApiError> {
+/// Gives `username` the next matric number and queues its webhook event on
+/// `conn`, leaving the commit to the caller.
+async fn set_matric_number(
+    conn: &mut sqlx::PgConnection,
+    username: &str,
+) -> Result<StudentResponse, ApiError> {
     let count = sqlx::query_scalar!(
         "SELECT COUNT(*) FROM students WHERE matric_number IS NOT NULL"
-    ).fetch_one(&state.pool).await.map_err(|_| ApiError::InternalServerError)?;
+    ).fetch_one(&mut *conn).await.map_err(|_| ApiError::InternalServerError)?;
     
     let matric_number = format!("MAT{:05}", count.unwrap_or(0) + 1);
 
@@ -134,14 +165,41 @@ async fn assign_matric_number(
         matric_number, 
         username
     )
-    .fetch_optional(&state.pool)
+    .fetch_optional(&mut *conn)
     .await
     .map_err(|_| ApiError::InternalServerError)?
     .ok_or_else(|| {
         ApiError::BadRequest("Student not found or already has matric number".to_string())
     })?;
 
-    Ok(Json(student.to_response()))
+    let response = student.to_response();
+    emit_event(conn, webhooks::STUDENT_MATRIC_ASSIGNED, &response).await?;
+    Ok(response)
+}
+
+async fn create_student(
+    State(state): State<AppState>, 
+    Json(payload): Json<CreateStudentRequest>) 
+    -> Result<Json<StudentResponse>, ApiError> {
+    let hashed_password = hash(&payload.password, DEFAULT_COST)
+        .map_err(|e| ApiError::BadRequest(format!("Password hashing failed: {}", e)))?;
+
+    let mut tx = state.pool.begin().await.map_err(|_| ApiError::InternalServerError)?;
+    let response = insert_student(&mut tx, &payload, &hashed_password).await?;
+    tx.commit().await.map_err(|_| ApiError::InternalServerError)?;
+
+    Ok(Json(response))
+}
+
+async fn assign_matric_number(
+    State(state): State<AppState>,
+    Path(username): Path<String> 
+) -> Result<Json<StudentResponse>, ApiError> {
+    let mut tx = state.pool.begin().await.map_err(|_| ApiError::InternalServerError)?;
+    let response = set_matric_number(&mut tx, &username).await?;
+    tx.commit().await.map_err(|_| ApiError::InternalServerError)?;
+
+    Ok(Json(response))
 }
 
 async fn list_students(State(state): State<AppState>) -> Result<Json<Vec<StudentResponse>>, ApiError> {  // Fixed: Vec<StudentResponse>
@@ -177,7 +235,7 @@ async fn get_student_by_matric(
     .fetch_optional(&state.pool)
     .await
     .map_err(|_| ApiError::InternalServerError)?
-    .ok_or(ApiError::NotFound)?;
+    .ok_or(ApiError::NotFound("Student"))?;
     
     Ok(Json(student.to_response()))
 }
@@ -206,7 +264,7 @@ async fn login(
     Ok(Json(student.to_response()))
 }
 async fn hello() -> String {
-    return "hello World".to_string();
+    "hello World".to_string()
 }
 
 #[tokio::main]
@@ -220,7 +278,13 @@ async fn main() -> anyhow::Result<()> {
         .await?;
 
     sqlx::migrate!().run(&pool).await?;
-    let app_state = AppState { pool };
+    notifications::spawn_sender(pool.clone());
+    webhooks::spawn_dispatcher(pool.clone());
+    let app_state = AppState {
+        pool,
+        storage: storage::Storage::new(config.storage_dir),
+        admin_token: config.admin_token,
+    };
 
     let app = Router::new()
         .route("/", get(hello))
@@ -229,7 +293,16 @@ async fn main() -> anyhow::Result<()> {
         .route("/students", get(list_students))
         .route("/students/matric/{matric_number}", get(get_student_by_matric))
         .route("/login", post(login))
-        .with_state(app_state);
+        .merge(branding::routes())
+        .merge(guardians::routes())
+        .merge(meetings::routes())
+        .merge(webhooks::routes())
+        .merge(reports::routes());
+
+    #[cfg(feature = "bench")]
+    let app = app.merge(bench::routes());
+
+    let app = app.with_state(app_state);
 
     let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await?;
     println!("Server running on http://localhost:3000");
@@ -0,0 +1,442 @@
+//! Parent-teacher meeting scheduling.
+//!
+//! Staff publish bookable slots per teacher (admin-only), and a free slot is
+//! booked for a student on behalf of one of the student's registered
+//! [guardians](crate::guardians), either by an admin or by the caller signing
+//! in with the student's password. Each booking queues a branded reminder in
+//! the [notification outbox](crate::notifications), due [`REMINDER_LEAD`]
+//! before the meeting. Overlapping slots for a teacher are rejected by an
+//! exclusion constraint; a slot takes one booking, and neither a student nor a
+//! guardian can hold two bookings that overlap in time.
+
+use axum::{
+    extract::{Path, Query, State},
+    response::{Json, Response},
+    routing::{get, post},
+    Router,
+};
+use std::sync::LazyLock;
+
+use bcrypt::{hash, verify, DEFAULT_COST};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::{
+    admin::Admin,
+    branding::Branding,
+    csv, emit_event,
+    guardians::{self, Guardian},
+    notifications, webhooks, ApiError, AppState,
+};
+
+/// How long before the meeting the guardian's reminder goes out.
+const REMINDER_LEAD: Duration = Duration::hours(24);
+
+/// Checked instead of a real hash when the matric number is unknown, so that
+/// case pays for the same bcrypt work as a wrong password.
+static UNKNOWN_STUDENT_HASH: LazyLock<String> =
+    LazyLock::new(|| hash("unknown-student", DEFAULT_COST).expect("bcrypt cost is valid"));
+
+/// First keys of the two-key advisory locks taken while booking.
+const STUDENT_LOCK_SPACE: i32 = 1;
+const GUARDIAN_LOCK_SPACE: i32 = 2;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CreateSlotRequest {
+    teacher: String,
+    starts_at: DateTime<Utc>,
+    ends_at: DateTime<Utc>,
+    location: Option<String>,
+}
+
+#[derive(Debug, FromRow)]
+struct MeetingSlot {
+    id: Uuid,
+    teacher: String,
+    starts_at: DateTime<Utc>,
+    ends_at: DateTime<Utc>,
+    location: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SlotResponse {
+    id: Uuid,
+    teacher: String,
+    starts_at: DateTime<Utc>,
+    ends_at: DateTime<Utc>,
+    location: Option<String>,
+    booked: bool,
+}
+
+impl MeetingSlot {
+    fn to_response(&self, booked: bool) -> SlotResponse {
+        SlotResponse {
+            id: self.id,
+            teacher: self.teacher.clone(),
+            starts_at: self.starts_at,
+            ends_at: self.ends_at,
+            location: self.location.clone(),
+            booked,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ListSlotsParams {
+    teacher: Option<String>,
+    #[serde(default)]
+    available: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CreateBookingRequest {
+    matric_number: String,
+    guardian_email: String,
+    /// The student's account password; not needed when an admin books.
+    password: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BookingResponse {
+    id: Uuid,
+    slot: SlotResponse,
+    student_name: String,
+    matric_number: String,
+    guardian_name: String,
+    guardian_email: String,
+    reminder_at: DateTime<Utc>,
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/meetings/slots", get(list_slots).post(create_slot))
+        .route("/meetings/slots/{slot_id}/bookings", post(book_slot))
+        .route("/meetings/teachers/{teacher}/agenda", get(export_agenda))
+}
+
+async fn create_slot(
+    _admin: Admin,
+    State(state): State<AppState>,
+    Json(payload): Json<CreateSlotRequest>,
+) -> Result<Json<SlotResponse>, ApiError> {
+    let teacher = payload.teacher.trim();
+    if teacher.is_empty() {
+        return Err(ApiError::BadRequest("teacher must not be empty".to_string()));
+    }
+    if payload.ends_at <= payload.starts_at {
+        return Err(ApiError::BadRequest("ends_at must be after starts_at".to_string()));
+    }
+    if payload.starts_at <= Utc::now() {
+        return Err(ApiError::BadRequest("starts_at must be in the future".to_string()));
+    }
+
+    let slot = sqlx::query_as!(
+        MeetingSlot,
+        r#"
+        INSERT INTO meeting_slots (teacher, starts_at, ends_at, location)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id, teacher, starts_at, ends_at, location
+        "#,
+        teacher,
+        payload.starts_at,
+        payload.ends_at,
+        payload.location
+    )
+    .fetch_one(&state.pool)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(err) if err.constraint() == Some("meeting_slots_no_overlap") => {
+            ApiError::Conflict("Slot overlaps another slot for this teacher")
+        }
+        _ => ApiError::InternalServerError,
+    })?;
+
+    Ok(Json(slot.to_response(false)))
+}
+
+async fn list_slots(
+    State(state): State<AppState>,
+    Query(params): Query<ListSlotsParams>,
+) -> Result<Json<Vec<SlotResponse>>, ApiError> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT s.id, s.teacher, s.starts_at, s.ends_at, s.location, b.id IS NOT NULL AS "booked!"
+        FROM meeting_slots s
+        LEFT JOIN meeting_bookings b ON b.slot_id = s.id
+        WHERE s.ends_at > NOW()
+          AND ($1::TEXT IS NULL OR s.teacher = $1)
+          AND (NOT $2 OR b.id IS NULL)
+        ORDER BY s.starts_at, s.teacher
+        "#,
+        params.teacher,
+        params.available
+    )
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|_| ApiError::InternalServerError)?;
+
+    Ok(Json(
+        rows.into_iter()
+            .map(|row| SlotResponse {
+                id: row.id,
+                teacher: row.teacher,
+                starts_at: row.starts_at,
+                ends_at: row.ends_at,
+                location: row.location,
+                booked: row.booked,
+            })
+            .collect(),
+    ))
+}
+
+/// Subject and body of the guardian's reminder, branded with the school's
+/// name and email footer once a theme has been configured.
+fn reminder_email(
+    branding: &Branding,
+    guardian: &Guardian,
+    slot: &MeetingSlot,
+    student_name: &str,
+) -> (String, String) {
+    let theme = branding.theme.as_ref();
+    let subject = match theme {
+        Some(theme) => format!("{}: parent-teacher meeting reminder", theme.school_name),
+        None => "Parent-teacher meeting reminder".to_string(),
+    };
+
+    let mut body = format!(
+        "Dear {}, this is a reminder of your meeting with {} about {} on {} at {}.",
+        guardian.name,
+        slot.teacher,
+        student_name,
+        slot.starts_at.format("%A %e %B %Y, %H:%M UTC"),
+        slot.location.as_deref().unwrap_or("school"),
+    );
+    if let Some(theme) = theme {
+        body.push_str(&format!("\n\n{}", theme.school_name));
+        if let Some(footer) = &theme.email_footer {
+            body.push_str(&format!("\n{footer}"));
+        }
+    }
+    (subject, body)
+}
+
+async fn book_slot(
+    admin: Option<Admin>,
+    State(state): State<AppState>,
+    Path(slot_id): Path<Uuid>,
+    Json(payload): Json<CreateBookingRequest>,
+) -> Result<Json<BookingResponse>, ApiError> {
+    let student = sqlx::query!(
+        "SELECT id, name, password FROM students WHERE matric_number = $1",
+        payload.matric_number
+    )
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(|_| ApiError::InternalServerError)?;
+
+    // Without an admin token the caller has to sign in as the student. An
+    // unknown matric number still runs a full `verify`, so it answers as slowly
+    // as a wrong password and the two cannot be told apart.
+    let student = match admin {
+        Some(Admin) => student.ok_or(ApiError::NotFound("Student"))?,
+        None => {
+            let password = payload.password.as_deref().ok_or(ApiError::Unauthorized)?;
+            let stored = student
+                .as_ref()
+                .map_or(UNKNOWN_STUDENT_HASH.as_str(), |student| student.password.as_str());
+            let valid = verify(password, stored).map_err(|_| ApiError::Unauthorized)?;
+            match student {
+                Some(student) if valid => student,
+                _ => return Err(ApiError::Unauthorized),
+            }
+        }
+    };
+
+    let guardian = sqlx::query_as!(
+        Guardian,
+        r#"
+        SELECT g.id, g.name, g.email
+        FROM guardians g
+        JOIN student_guardians sg ON sg.guardian_id = g.id
+        WHERE sg.student_id = $1 AND g.email = $2
+        "#,
+        student.id,
+        guardians::normalize_email(&payload.guardian_email)
+    )
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(|_| ApiError::InternalServerError)?
+    .ok_or(ApiError::NotFound("Guardian for this student"))?;
+
+    let branding = Branding::load(&state.pool).await?;
+
+    let mut tx = state
+        .pool
+        .begin()
+        .await
+        .map_err(|_| ApiError::InternalServerError)?;
+
+    let slot = sqlx::query_as!(
+        MeetingSlot,
+        r#"
+        SELECT id, teacher, starts_at, ends_at, location
+        FROM meeting_slots
+        WHERE id = $1
+        FOR UPDATE
+        "#,
+        slot_id
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|_| ApiError::InternalServerError)?
+    .ok_or(ApiError::NotFound("Meeting slot"))?;
+
+    if slot.starts_at <= Utc::now() {
+        return Err(ApiError::BadRequest("Meeting slot has already started".to_string()));
+    }
+
+    // Serialise bookings per student, then per guardian, so two concurrent
+    // requests for different slots cannot both pass the overlap check below.
+    // Locks are always taken in this order, which rules out deadlocks.
+    sqlx::query!(
+        r#"
+        SELECT pg_advisory_xact_lock($1, hashtext($2::UUID::TEXT)),
+               pg_advisory_xact_lock($3, hashtext($4::UUID::TEXT))
+        "#,
+        STUDENT_LOCK_SPACE,
+        student.id,
+        GUARDIAN_LOCK_SPACE,
+        guardian.id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|_| ApiError::InternalServerError)?;
+
+    let overlap = sqlx::query!(
+        r#"
+        SELECT
+            EXISTS (
+                SELECT 1
+                FROM meeting_bookings b
+                JOIN meeting_slots s ON s.id = b.slot_id
+                WHERE b.student_id = $1 AND s.starts_at < $4 AND s.ends_at > $3
+            ) AS "student!",
+            EXISTS (
+                SELECT 1
+                FROM meeting_bookings b
+                JOIN meeting_slots s ON s.id = b.slot_id
+                WHERE b.guardian_id = $2 AND s.starts_at < $4 AND s.ends_at > $3
+            ) AS "guardian!"
+        "#,
+        student.id,
+        guardian.id,
+        slot.starts_at,
+        slot.ends_at
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|_| ApiError::InternalServerError)?;
+    if overlap.student {
+        return Err(ApiError::Conflict("Student already has a meeting at this time"));
+    }
+    if overlap.guardian {
+        return Err(ApiError::Conflict("Guardian already has a meeting at this time"));
+    }
+
+    let booking_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO meeting_bookings (slot_id, student_id, guardian_id)
+        VALUES ($1, $2, $3)
+        RETURNING id
+        "#,
+        slot.id,
+        student.id,
+        guardian.id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(err) if err.constraint() == Some("meeting_bookings_slot_id_key") => {
+            ApiError::Conflict("Meeting slot is already booked")
+        }
+        _ => ApiError::InternalServerError,
+    })?;
+
+    let reminder_at = (slot.starts_at - REMINDER_LEAD).max(Utc::now());
+    let (subject, body) = reminder_email(&branding, &guardian, &slot, &student.name);
+    notifications::enqueue(&mut *tx, &guardian.email, &subject, &body, reminder_at)
+        .await
+        .map_err(|_| ApiError::InternalServerError)?;
+
+    let booking = BookingResponse {
+        id: booking_id,
+        slot: slot.to_response(true),
+        student_name: student.name,
+        matric_number: payload.matric_number,
+        guardian_name: guardian.name,
+        guardian_email: guardian.email,
+        reminder_at,
+    };
+    emit_event(&mut tx, webhooks::MEETING_BOOKED, &booking).await?;
+
+    tx.commit().await.map_err(|_| ApiError::InternalServerError)?;
+
+    Ok(Json(booking))
+}
+
+/// Every upcoming slot for `teacher` as CSV, with booking details filled in
+/// for the slots that have been taken.
+async fn export_agenda(
+    _admin: Admin,
+    State(state): State<AppState>,
+    Path(teacher): Path<String>,
+) -> Result<Response, ApiError> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT s.starts_at, s.ends_at, s.location,
+               st.name AS "student_name?", st.matric_number AS "matric_number?",
+               g.name AS "guardian_name?", g.email AS "guardian_email?"
+        FROM meeting_slots s
+        LEFT JOIN meeting_bookings b ON b.slot_id = s.id
+        LEFT JOIN students st ON st.id = b.student_id
+        LEFT JOIN guardians g ON g.id = b.guardian_id
+        WHERE s.teacher = $1 AND s.ends_at > NOW()
+        ORDER BY s.starts_at
+        "#,
+        teacher
+    )
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|_| ApiError::InternalServerError)?;
+
+    let body = csv::encode(
+        &[
+            "starts_at",
+            "ends_at",
+            "location",
+            "student_name",
+            "matric_number",
+            "guardian_name",
+            "guardian_email",
+        ],
+        rows.into_iter().map(|row| {
+            [
+                row.starts_at.to_rfc3339(),
+                row.ends_at.to_rfc3339(),
+                row.location.unwrap_or_default(),
+                row.student_name.unwrap_or_default(),
+                row.matric_number.unwrap_or_default(),
+                row.guardian_name.unwrap_or_default(),
+                row.guardian_email.unwrap_or_default(),
+            ]
+        }),
+    );
+
+    let filename: String = teacher
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    Ok(csv::attachment(&format!("agenda_{filename}.csv"), body))
+}
@@ -0,0 +1,106 @@
+//! Outbox for outgoing messages such as meeting reminders.
+//!
+//! Features enqueue rows in `notifications`, usually inside the same
+//! transaction as the change that triggered them. [`spawn_sender`] starts a
+//! background task that picks up rows whose `send_at` has passed, hands each to
+//! [`deliver`] and stamps `sent_at`, so every queued message goes out once.
+//!
+//! There is no mail transport configured yet, so [`deliver`] writes the message
+//! to stdout. Replacing that function is all a real transport needs.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, PgExecutor, PgPool};
+use uuid::Uuid;
+
+/// How often the sender looks for messages that are due.
+const POLL_INTERVAL: Duration = Duration::from_secs(15);
+/// Most messages sent per poll; the rest wait for the next one.
+const BATCH_SIZE: i64 = 100;
+
+#[derive(Debug, FromRow)]
+struct Notification {
+    id: Uuid,
+    recipient: String,
+    subject: String,
+    body: String,
+}
+
+pub async fn enqueue<'e>(
+    executor: impl PgExecutor<'e>,
+    recipient: &str,
+    subject: &str,
+    body: &str,
+    send_at: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO notifications (recipient, subject, body, send_at)
+        VALUES ($1, $2, $3, $4)
+        "#,
+        recipient,
+        subject,
+        body,
+        send_at
+    )
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// Starts the background sender; it runs for as long as the server does.
+pub fn spawn_sender(pool: PgPool) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(e) = send_due(&pool).await {
+                eprintln!("⚠️ Sending notifications failed: {e}");
+            }
+        }
+    });
+}
+
+/// Sends one batch of due messages. Rows are locked with `SKIP LOCKED`, so
+/// several servers can poll the same table without sending a message twice.
+async fn send_due(pool: &PgPool) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let due = sqlx::query_as!(
+        Notification,
+        r#"
+        SELECT id, recipient, subject, body
+        FROM notifications
+        WHERE sent_at IS NULL AND send_at <= NOW()
+        ORDER BY send_at
+        LIMIT $1
+        FOR UPDATE SKIP LOCKED
+        "#,
+        BATCH_SIZE
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    for notification in &due {
+        deliver(notification);
+    }
+
+    let ids: Vec<Uuid> = due.iter().map(|notification| notification.id).collect();
+    sqlx::query!(
+        "UPDATE notifications SET sent_at = NOW() WHERE id = ANY($1)",
+        &ids
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+fn deliver(notification: &Notification) {
+    println!(
+        "📧 To: {}\nSubject: {}\n\n{}\n",
+        notification.recipient, notification.subject, notification.body
+    );
+}
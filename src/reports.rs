@@ -0,0 +1,354 @@
+//! Admin-only reporting over named, parameterised SQL templates.
+//!
+//! Templates are registered in [`REPORTS`] and nowhere else, so nobody can send
+//! SQL of their own. `GET /reports` lists them and `GET /reports/{name}` runs one
+//! with its parameters taken from the query string, returning CSV. Each run
+//! happens in a read-only transaction with a statement timeout, and at most
+//! `limit` rows (default [`DEFAULT_ROW_LIMIT`], capped at [`MAX_ROW_LIMIT`]) are
+//! returned; `X-Report-Truncated: true` is set when more were available.
+//!
+//! Template SQL binds its parameters as `$1..$n` in declaration order, with
+//! optional parameters bound as NULL when absent. Every parameter needs an
+//! explicit cast (`$1::DATE`) and every selected column must be cast to TEXT.
+//! Each template ends with its own `ORDER BY` and `LIMIT $n+1::BIGINT`, the
+//! handler binding the row cap there, so truncation always keeps the first rows
+//! in the template's order.
+
+use std::collections::HashMap;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::HeaderValue,
+    response::{Json, Response},
+    routing::get,
+    Router,
+};
+use chrono::NaiveDate;
+use serde::Serialize;
+use sqlx::{postgres::PgRow, Column, Executor, Row, Statement};
+
+use crate::{admin::Admin, csv, ApiError, AppState};
+
+const DEFAULT_ROW_LIMIT: i64 = 1_000;
+const MAX_ROW_LIMIT: i64 = 50_000;
+const STATEMENT_TIMEOUT: &str = "SET LOCAL statement_timeout = '15s'";
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum ParamKind {
+    Text,
+    Integer,
+    Date,
+    Boolean,
+}
+
+#[derive(Debug, Serialize)]
+struct ReportParam {
+    name: &'static str,
+    kind: ParamKind,
+    required: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct ReportTemplate {
+    name: &'static str,
+    description: &'static str,
+    params: &'static [ReportParam],
+    sql: &'static str,
+}
+
+const REPORTS: &[ReportTemplate] = &[
+    ReportTemplate {
+        name: "students",
+        description: "Students with their matric numbers, optionally filtered by creation date",
+        params: &[
+            ReportParam { name: "created_from", kind: ParamKind::Date, required: false },
+            ReportParam { name: "created_to", kind: ParamKind::Date, required: false },
+            ReportParam { name: "has_matric", kind: ParamKind::Boolean, required: false },
+        ],
+        sql: r#"
+        SELECT username::TEXT, name::TEXT, matric_number::TEXT, created_at::DATE::TEXT AS created_on
+        FROM students
+        WHERE ($1::DATE IS NULL OR created_at >= $1::DATE)
+          AND ($2::DATE IS NULL OR created_at < $2::DATE + 1)
+          AND ($3::BOOLEAN IS NULL OR (matric_number IS NOT NULL) = $3)
+        ORDER BY created_at, username
+        LIMIT $4::BIGINT
+        "#,
+    },
+    ReportTemplate {
+        name: "enrolment_by_month",
+        description: "New students and matric numbers assigned per month for a year",
+        params: &[ReportParam { name: "year", kind: ParamKind::Integer, required: true }],
+        sql: r#"
+        SELECT TO_CHAR(DATE_TRUNC('month', created_at), 'YYYY-MM') AS month,
+               COUNT(*)::TEXT AS students,
+               COUNT(matric_number)::TEXT AS with_matric_number
+        FROM students
+        WHERE EXTRACT(YEAR FROM created_at) = $1::BIGINT
+        GROUP BY DATE_TRUNC('month', created_at)
+        ORDER BY DATE_TRUNC('month', created_at)
+        LIMIT $2::BIGINT
+        "#,
+    },
+    ReportTemplate {
+        name: "meeting_bookings",
+        description: "Parent-teacher meeting bookings, optionally for one teacher or from a date",
+        params: &[
+            ReportParam { name: "teacher", kind: ParamKind::Text, required: false },
+            ReportParam { name: "from", kind: ParamKind::Date, required: false },
+        ],
+        sql: r#"
+        SELECT s.teacher::TEXT, s.starts_at::TEXT, s.ends_at::TEXT, s.location::TEXT,
+               st.name::TEXT AS student_name, st.matric_number::TEXT,
+               g.name::TEXT AS guardian_name, g.email::TEXT AS guardian_email
+        FROM meeting_bookings b
+        JOIN meeting_slots s ON s.id = b.slot_id
+        JOIN students st ON st.id = b.student_id
+        JOIN guardians g ON g.id = b.guardian_id
+        WHERE ($1::TEXT IS NULL OR s.teacher = $1)
+          AND ($2::DATE IS NULL OR s.starts_at >= $2::DATE)
+        ORDER BY s.starts_at, s.teacher
+        LIMIT $3::BIGINT
+        "#,
+    },
+];
+
+/// A parameter value parsed from the query string, ready to bind.
+#[derive(Debug, PartialEq)]
+enum ParamValue {
+    Text(Option<String>),
+    Integer(Option<i64>),
+    Date(Option<NaiveDate>),
+    Boolean(Option<bool>),
+}
+
+impl ReportParam {
+    fn parse(&self, raw: Option<&str>) -> Result<ParamValue, ApiError> {
+        let raw = raw.map(str::trim).filter(|value| !value.is_empty());
+        if raw.is_none() && self.required {
+            return Err(ApiError::BadRequest(format!("Missing required parameter '{}'", self.name)));
+        }
+        let invalid = |expected: &str| {
+            ApiError::BadRequest(format!("Parameter '{}' must be {expected}", self.name))
+        };
+
+        Ok(match self.kind {
+            ParamKind::Text => ParamValue::Text(raw.map(str::to_string)),
+            ParamKind::Integer => ParamValue::Integer(
+                raw.map(|v| v.parse().map_err(|_| invalid("an integer")))
+                    .transpose()?,
+            ),
+            ParamKind::Date => ParamValue::Date(
+                raw.map(|v| {
+                    NaiveDate::parse_from_str(v, "%Y-%m-%d").map_err(|_| invalid("a date (YYYY-MM-DD)"))
+                })
+                .transpose()?,
+            ),
+            ParamKind::Boolean => ParamValue::Boolean(
+                raw.map(|v| v.parse().map_err(|_| invalid("true or false")))
+                    .transpose()?,
+            ),
+        })
+    }
+}
+
+impl ReportTemplate {
+    /// Splits the query string into the row limit and one value per declared
+    /// parameter, rejecting anything the template does not declare.
+    fn parse_query(
+        &self,
+        mut query: HashMap<String, String>,
+    ) -> Result<(i64, Vec<ParamValue>), ApiError> {
+        let limit = match query.remove("limit") {
+            Some(raw) => raw
+                .parse::<i64>()
+                .ok()
+                .filter(|limit| (1..=MAX_ROW_LIMIT).contains(limit))
+                .ok_or_else(|| {
+                    ApiError::BadRequest(format!("limit must be between 1 and {MAX_ROW_LIMIT}"))
+                })?,
+            None => DEFAULT_ROW_LIMIT,
+        };
+        if let Some(unknown) = query
+            .keys()
+            .find(|key| !self.params.iter().any(|param| param.name == key.as_str()))
+        {
+            return Err(ApiError::BadRequest(format!(
+                "Unknown parameter '{unknown}' for report '{}'",
+                self.name
+            )));
+        }
+        let values = self
+            .params
+            .iter()
+            .map(|param| param.parse(query.get(param.name).map(String::as_str)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok((limit, values))
+    }
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/reports", get(list_reports))
+        .route("/reports/{name}", get(run_report))
+}
+
+async fn list_reports(_admin: Admin) -> Json<&'static [ReportTemplate]> {
+    Json(REPORTS)
+}
+
+async fn run_report(
+    _admin: Admin,
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Result<Response, ApiError> {
+    let report = REPORTS
+        .iter()
+        .find(|report| report.name == name)
+        .ok_or(ApiError::NotFound("Report"))?;
+
+    let (limit, values) = report.parse_query(query)?;
+
+    let mut tx = state
+        .pool
+        .begin()
+        .await
+        .map_err(|_| ApiError::InternalServerError)?;
+    for setting in ["SET TRANSACTION READ ONLY", STATEMENT_TIMEOUT] {
+        tx.execute(setting)
+            .await
+            .map_err(|_| ApiError::InternalServerError)?;
+    }
+
+    let statement = (&mut *tx)
+        .prepare(report.sql)
+        .await
+        .map_err(|_| ApiError::InternalServerError)?;
+    let header: Vec<&str> = statement.columns().iter().map(Column::name).collect();
+
+    let mut query = statement.query();
+    for value in values {
+        query = match value {
+            ParamValue::Text(v) => query.bind(v),
+            ParamValue::Integer(v) => query.bind(v),
+            ParamValue::Date(v) => query.bind(v),
+            ParamValue::Boolean(v) => query.bind(v),
+        };
+    }
+    // Ask for one row more than the limit to find out whether we truncated.
+    let query = query.bind(limit + 1);
+    let mut rows: Vec<PgRow> = query
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|_| ApiError::InternalServerError)?;
+    tx.rollback().await.map_err(|_| ApiError::InternalServerError)?;
+
+    let truncated = rows.len() as i64 > limit;
+    rows.truncate(limit as usize);
+    let records = rows
+        .iter()
+        .map(|row| {
+            (0..row.len())
+                .map(|i| row.try_get::<Option<String>, _>(i).map(Option::unwrap_or_default))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| ApiError::InternalServerError)?;
+
+    let mut response = csv::attachment(&format!("{}.csv", report.name), csv::encode(&header, records));
+    if truncated {
+        response
+            .headers_mut()
+            .insert("x-report-truncated", HeaderValue::from_static("true"));
+    }
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: ReportParam = ReportParam { name: "t", kind: ParamKind::Text, required: false };
+    const INTEGER: ReportParam = ReportParam { name: "i", kind: ParamKind::Integer, required: true };
+    const DATE: ReportParam = ReportParam { name: "d", kind: ParamKind::Date, required: false };
+    const BOOLEAN: ReportParam = ReportParam { name: "b", kind: ParamKind::Boolean, required: true };
+
+    fn report(name: &str) -> &'static ReportTemplate {
+        REPORTS.iter().find(|report| report.name == name).unwrap()
+    }
+
+    fn query(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn parse_accepts_valid_values() {
+        assert_eq!(TEXT.parse(Some(" hi ")).unwrap(), ParamValue::Text(Some("hi".to_string())));
+        assert_eq!(INTEGER.parse(Some("2026")).unwrap(), ParamValue::Integer(Some(2026)));
+        assert_eq!(
+            DATE.parse(Some("2026-10-14")).unwrap(),
+            ParamValue::Date(NaiveDate::from_ymd_opt(2026, 10, 14))
+        );
+        assert_eq!(BOOLEAN.parse(Some("false")).unwrap(), ParamValue::Boolean(Some(false)));
+    }
+
+    #[test]
+    fn parse_treats_empty_as_missing() {
+        assert_eq!(TEXT.parse(None).unwrap(), ParamValue::Text(None));
+        assert_eq!(TEXT.parse(Some("  ")).unwrap(), ParamValue::Text(None));
+        assert_eq!(DATE.parse(Some("")).unwrap(), ParamValue::Date(None));
+        assert!(matches!(INTEGER.parse(None), Err(ApiError::BadRequest(_))));
+        assert!(matches!(INTEGER.parse(Some("")), Err(ApiError::BadRequest(_))));
+        assert!(matches!(BOOLEAN.parse(Some(" ")), Err(ApiError::BadRequest(_))));
+    }
+
+    #[test]
+    fn parse_rejects_invalid_values_per_kind() {
+        assert!(matches!(INTEGER.parse(Some("12a")), Err(ApiError::BadRequest(_))));
+        assert!(matches!(DATE.parse(Some("14/10/2026")), Err(ApiError::BadRequest(_))));
+        assert!(matches!(DATE.parse(Some("2026-02-30")), Err(ApiError::BadRequest(_))));
+        assert!(matches!(BOOLEAN.parse(Some("yes")), Err(ApiError::BadRequest(_))));
+    }
+
+    #[test]
+    fn parse_query_rejects_unknown_parameters() {
+        let students = report("students");
+        let result = students.parse_query(query(&[("password", "x")]));
+        assert!(matches!(result, Err(ApiError::BadRequest(message)) if message.contains("'password'")));
+    }
+
+    #[test]
+    fn parse_query_reads_limit_and_params_in_order() {
+        let students = report("students");
+        let (limit, values) = students
+            .parse_query(query(&[("has_matric", "true"), ("limit", "10")]))
+            .unwrap();
+        assert_eq!(limit, 10);
+        assert_eq!(
+            values,
+            vec![ParamValue::Date(None), ParamValue::Date(None), ParamValue::Boolean(Some(true))]
+        );
+
+        let (limit, _) = students.parse_query(query(&[])).unwrap();
+        assert_eq!(limit, DEFAULT_ROW_LIMIT);
+
+        for bad in ["0", "-1", "abc", &(MAX_ROW_LIMIT + 1).to_string()] {
+            let result = students.parse_query(query(&[("limit", bad)]));
+            assert!(matches!(result, Err(ApiError::BadRequest(_))), "limit={bad}");
+        }
+    }
+
+    #[test]
+    fn templates_end_with_limit_placeholder() {
+        for report in REPORTS {
+            let expected = format!("LIMIT ${}::BIGINT", report.params.len() + 1);
+            assert!(
+                report.sql.trim_end().ends_with(&expected),
+                "report '{}' must end with {expected}",
+                report.name
+            );
+        }
+    }
+}
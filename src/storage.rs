@@ -0,0 +1,129 @@
+//! Blob storage for uploaded files, backed by a directory on local disk.
+//!
+//! Callers address blobs by slash-separated keys such as `branding/logo`; keys
+//! are validated so they can never escape the storage root.
+
+use std::{io, path::PathBuf};
+
+#[derive(Debug, Clone)]
+pub struct Storage {
+    root: PathBuf,
+}
+
+impl Storage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Storage { root: root.into() }
+    }
+
+    fn path_for(&self, key: &str) -> io::Result<PathBuf> {
+        let valid = !key.is_empty()
+            && key.split('/').all(|segment| {
+                !segment.is_empty()
+                    && segment != "."
+                    && segment != ".."
+                    && segment
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+            });
+        if !valid {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid storage key: {key}"),
+            ));
+        }
+        Ok(self.root.join(key))
+    }
+
+    pub async fn put(&self, key: &str, bytes: &[u8]) -> io::Result<()> {
+        let path = self.path_for(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(path, bytes).await
+    }
+
+    /// Removing a key that holds nothing is not an error.
+    pub async fn delete(&self, key: &str) -> io::Result<()> {
+        match tokio::fs::remove_file(self.path_for(key)?).await {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            result => result,
+        }
+    }
+
+    /// Returns `Ok(None)` when nothing is stored under `key`.
+    pub async fn get(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        match tokio::fs::read(self.path_for(key)?).await {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn storage() -> Storage {
+        Storage::new("/srv/storage")
+    }
+
+    fn rejects(key: &str) -> bool {
+        storage()
+            .path_for(key)
+            .is_err_and(|e| e.kind() == io::ErrorKind::InvalidInput)
+    }
+
+    #[test]
+    fn keys_resolve_under_the_root() {
+        assert_eq!(
+            storage().path_for("branding/logo-1f2e.png").unwrap(),
+            PathBuf::from("/srv/storage/branding/logo-1f2e.png")
+        );
+        assert_eq!(storage().path_for("a_b").unwrap(), PathBuf::from("/srv/storage/a_b"));
+    }
+
+    #[test]
+    fn rejects_dot_segments() {
+        assert!(rejects(".."));
+        assert!(rejects("../etc/passwd"));
+        assert!(rejects("branding/../../etc/passwd"));
+        assert!(rejects("branding/.."));
+        assert!(rejects("./logo"));
+        // Dots inside a segment are fine.
+        assert!(!rejects("branding/..logo"));
+    }
+
+    #[test]
+    fn rejects_empty_segments_and_absolute_keys() {
+        assert!(rejects(""));
+        assert!(rejects("/etc/passwd"));
+        assert!(rejects("branding//logo"));
+        assert!(rejects("branding/"));
+    }
+
+    #[test]
+    fn rejects_disallowed_characters() {
+        assert!(rejects("branding\\..\\logo"));
+        assert!(rejects("branding/lo go"));
+        assert!(rejects("branding/logo\0"));
+        assert!(rejects("branding/~logo"));
+        assert!(rejects("brandïng/logo"));
+    }
+
+    #[tokio::test]
+    async fn put_get_and_delete_round_trip() {
+        let root = std::env::temp_dir().join(format!("storage-test-{}", std::process::id()));
+        let storage = Storage::new(&root);
+
+        storage.put("branding/logo", b"png").await.unwrap();
+        assert_eq!(storage.get("branding/logo").await.unwrap(), Some(b"png".to_vec()));
+        storage.delete("branding/logo").await.unwrap();
+        assert_eq!(storage.get("branding/logo").await.unwrap(), None);
+        // Deleting again is not an error.
+        storage.delete("branding/logo").await.unwrap();
+        assert!(storage.put("../escape", b"x").await.is_err());
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
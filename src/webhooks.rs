@@ -0,0 +1,863 @@
+//! Webhook subscriptions with per-subscription event filters and payload shaping.
+//!
+//! Handlers call [`emit`] inside their transaction; every subscription whose
+//! `event_types` filter matches gets a row in `webhook_deliveries` holding the
+//! payload exactly as it should be posted to its `url`. The dispatcher started
+//! by [`spawn_dispatcher`] posts due rows, stamps `delivered_at` on a 2xx reply
+//! and otherwise retries with backoff, up to [`MAX_ATTEMPTS`] times.
+//!
+//! `POST /webhooks/{id}/test` posts a sample event to the subscription's `url`
+//! straight away and reports the response; `POST /webhooks/{id}/preview` only
+//! shows the payload that would be sent.
+//!
+//! Events are enveloped as `{"id", "event", "occurred_at", "data"}`. A
+//! subscription can narrow that down in one of two ways:
+//!
+//! - `fields`: paths such as `$.event` or `data.username` to keep; the result
+//!   keeps the nesting of the original event.
+//! - `template`: any JSON value. A string that is exactly `{{path}}` is replaced
+//!   by the value at that path, other strings have each `{{path}}` substituted
+//!   as text. A `{{` without a closing `}}` is kept literally.
+//!
+//! With neither set the whole envelope is sent. Filters are event types like
+//! `student.created` or prefixes like `student.*`; an empty list matches all.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+    routing::{get, post},
+    Router,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use sqlx::{FromRow, PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::{
+    admin::Admin,
+    http::{self, HttpError},
+    ApiError, AppState,
+};
+
+pub const STUDENT_CREATED: &str = "student.created";
+pub const STUDENT_MATRIC_ASSIGNED: &str = "student.matric_assigned";
+pub const MEETING_BOOKED: &str = "meeting.booked";
+
+const EVENT_TYPES: &[&str] = &[STUDENT_CREATED, STUDENT_MATRIC_ASSIGNED, MEETING_BOOKED];
+
+/// How often the dispatcher looks for deliveries that are due.
+const DISPATCH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+/// Most deliveries posted per poll; the rest wait for the next one.
+const DISPATCH_BATCH_SIZE: i64 = 20;
+/// Attempts after which a delivery is given up on and left undelivered.
+const MAX_ATTEMPTS: i32 = 8;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SubscriptionRequest {
+    url: String,
+    #[serde(default)]
+    event_types: Vec<String>,
+    fields: Option<Vec<String>>,
+    template: Option<Value>,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+struct Subscription {
+    id: Uuid,
+    url: String,
+    event_types: Vec<String>,
+    fields: Option<Vec<String>>,
+    template: Option<Value>,
+    created_at: Option<DateTime<Utc>>,
+}
+
+/// Body of the preview and test endpoints; both fields are optional.
+#[derive(Debug, Default, Deserialize)]
+struct SampleRequest {
+    event_type: Option<String>,
+    data: Option<Value>,
+}
+
+#[derive(Debug, Serialize)]
+struct PreviewResponse {
+    event_type: String,
+    matched: bool,
+    payload: Option<Value>,
+}
+
+#[derive(Debug, Serialize)]
+struct TestDeliveryResponse {
+    event_type: String,
+    matched: bool,
+    payload: Option<Value>,
+    /// Status the subscriber answered with, if it answered at all.
+    status: Option<u16>,
+    error: Option<String>,
+}
+
+#[derive(Debug, FromRow)]
+struct DueDelivery {
+    id: Uuid,
+    url: String,
+    event_type: String,
+    payload: Value,
+    attempts: i32,
+}
+
+impl Subscription {
+    fn matches(&self, event_type: &str) -> bool {
+        self.event_types.is_empty()
+            || self
+                .event_types
+                .iter()
+                .any(|filter| filter_matches(filter, event_type))
+    }
+
+    fn shape(&self, event: &Value) -> Value {
+        if let Some(template) = &self.template {
+            render_template(template, event)
+        } else if let Some(fields) = &self.fields {
+            select_fields(event, fields)
+        } else {
+            event.clone()
+        }
+    }
+}
+
+fn filter_matches(filter: &str, event_type: &str) -> bool {
+    match filter.strip_suffix('*') {
+        Some(prefix) => prefix.ends_with('.') && event_type.starts_with(prefix),
+        None => filter == event_type,
+    }
+}
+
+/// Splits `$.a.b`, `$a.b` or `a.b` into segments; `None` if the path is malformed.
+fn parse_path(path: &str) -> Option<Vec<&str>> {
+    let path = path.trim();
+    let path = path
+        .strip_prefix("$.")
+        .or_else(|| path.strip_prefix('$'))
+        .unwrap_or(path);
+    let segments: Vec<&str> = path.split('.').collect();
+    if segments.iter().any(|segment| segment.is_empty()) {
+        return None;
+    }
+    Some(segments)
+}
+
+/// Follows object keys, or array indices for numeric segments.
+fn lookup<'a>(value: &'a Value, segments: &[&str]) -> Option<&'a Value> {
+    segments.iter().try_fold(value, |current, segment| match current {
+        Value::Object(map) => map.get(*segment),
+        Value::Array(items) => segment.parse::<usize>().ok().and_then(|i| items.get(i)),
+        _ => None,
+    })
+}
+
+fn select_fields(event: &Value, fields: &[String]) -> Value {
+    let mut selected = Map::new();
+    for field in fields {
+        let Some(segments) = parse_path(field) else {
+            continue;
+        };
+        let Some(value) = lookup(event, &segments) else {
+            continue;
+        };
+        let (last, parents) = segments.split_last().expect("paths have a segment");
+        let mut target = Some(&mut selected);
+        for segment in parents {
+            // An earlier field may already have put a non-object value here.
+            target = target.and_then(|map| {
+                map.entry(segment.to_string())
+                    .or_insert_with(|| Value::Object(Map::new()))
+                    .as_object_mut()
+            });
+        }
+        if let Some(map) = target {
+            map.insert(last.to_string(), value.clone());
+        }
+    }
+    Value::Object(selected)
+}
+
+fn render_template(template: &Value, event: &Value) -> Value {
+    match template {
+        Value::String(text) => render_string(text, event),
+        Value::Array(items) => Value::Array(items.iter().map(|t| render_template(t, event)).collect()),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, t)| (key.clone(), render_template(t, event)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+/// The paths inside each complete `{{...}}` in `text`, in order.
+fn placeholders(text: &str) -> Vec<&str> {
+    let mut paths = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        paths.push(&rest[start + 2..start + end]);
+        rest = &rest[start + end + 2..];
+    }
+    paths
+}
+
+/// The first placeholder path in `template` that [`parse_path`] rejects.
+fn invalid_template_path(template: &Value) -> Option<&str> {
+    match template {
+        Value::String(text) => placeholders(text)
+            .into_iter()
+            .find(|path| parse_path(path).is_none()),
+        Value::Array(items) => items.iter().find_map(invalid_template_path),
+        Value::Object(map) => map.values().find_map(invalid_template_path),
+        _ => None,
+    }
+}
+
+fn render_string(text: &str, event: &Value) -> Value {
+    let resolve = |path: &str| parse_path(path).and_then(|segments| lookup(event, &segments));
+
+    // A lone placeholder keeps the JSON type of the value it points at.
+    if let Some(path) = text
+        .strip_prefix("{{")
+        .and_then(|rest| rest.strip_suffix("}}"))
+        .filter(|path| !path.contains("{{") && !path.contains("}}"))
+    {
+        return resolve(path).cloned().unwrap_or(Value::Null);
+    }
+
+    let mut rendered = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        rendered.push_str(&rest[..start]);
+        match resolve(&rest[start + 2..start + end]) {
+            Some(Value::String(s)) => rendered.push_str(s),
+            Some(Value::Null) | None => {}
+            Some(other) => rendered.push_str(&other.to_string()),
+        }
+        rest = &rest[start + end + 2..];
+    }
+    rendered.push_str(rest);
+    Value::String(rendered)
+}
+
+fn envelope(event_type: &str, data: Value) -> Value {
+    json!({
+        "id": Uuid::new_v4(),
+        "event": event_type,
+        "occurred_at": Utc::now(),
+        "data": data,
+    })
+}
+
+async fn enqueue_delivery(
+    conn: &mut PgConnection,
+    subscription: &Subscription,
+    event_type: &str,
+    event: &Value,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO webhook_deliveries (subscription_id, event_type, payload)
+        VALUES ($1, $2, $3)
+        "#,
+        subscription.id,
+        event_type,
+        subscription.shape(event)
+    )
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+/// Queues `data` as an `event_type` event for every subscription that wants it.
+pub async fn emit(conn: &mut PgConnection, event_type: &str, data: Value) -> Result<(), sqlx::Error> {
+    let subscriptions = sqlx::query_as!(
+        Subscription,
+        "SELECT id, url, event_types, fields, template, created_at FROM webhook_subscriptions"
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let event = envelope(event_type, data);
+    for subscription in subscriptions.iter().filter(|s| s.matches(event_type)) {
+        enqueue_delivery(conn, subscription, event_type, &event).await?;
+    }
+    Ok(())
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/webhooks", get(list_subscriptions).post(create_subscription))
+        .route(
+            "/webhooks/{id}",
+            get(get_subscription)
+                .put(update_subscription)
+                .delete(delete_subscription),
+        )
+        .route("/webhooks/{id}/preview", post(preview))
+        .route("/webhooks/{id}/test", post(test_delivery))
+}
+
+/// Headers sent with every post, test deliveries included.
+fn delivery_headers(event_type: &str, delivery_id: String) -> Vec<(&'static str, String)> {
+    vec![
+        ("X-Webhook-Event", event_type.to_string()),
+        ("X-Webhook-Delivery", delivery_id),
+    ]
+}
+
+/// Why a post did not count as delivered, or `None` if the subscriber
+/// answered with a 2xx status.
+fn delivery_error(outcome: &Result<u16, HttpError>) -> Option<String> {
+    match outcome {
+        Ok(status) if (200..300).contains(status) => None,
+        Ok(status) => Some(format!("subscriber answered with HTTP {status}")),
+        Err(e) => Some(e.to_string()),
+    }
+}
+
+/// Wait before the next try after `attempts` failed ones: 30 seconds,
+/// doubling each time, capped at an hour.
+fn retry_delay(attempts: i32) -> Duration {
+    let doublings = attempts.clamp(1, 8) - 1;
+    Duration::seconds(30 << doublings).min(Duration::hours(1))
+}
+
+/// Starts the background dispatcher; it runs for as long as the server does.
+pub fn spawn_dispatcher(pool: PgPool) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(DISPATCH_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(e) = dispatch_due(&pool).await {
+                eprintln!("⚠️ Dispatching webhooks failed: {e}");
+            }
+        }
+    });
+}
+
+/// Posts one batch of due deliveries. Each is claimed first by counting the
+/// attempt and pushing `next_attempt_at` out, so no transaction stays open
+/// while posting, other servers skip it, and a crash mid-post only delays it.
+async fn dispatch_due(pool: &PgPool) -> Result<(), sqlx::Error> {
+    let due = sqlx::query_as!(
+        DueDelivery,
+        r#"
+        UPDATE webhook_deliveries d
+        SET attempts = d.attempts + 1,
+            next_attempt_at = NOW() + INTERVAL '5 minutes'
+        FROM webhook_subscriptions s
+        WHERE s.id = d.subscription_id
+          AND d.id IN (
+              SELECT id FROM webhook_deliveries
+              WHERE next_attempt_at <= NOW()
+              ORDER BY next_attempt_at
+              LIMIT $1
+              FOR UPDATE SKIP LOCKED
+          )
+        RETURNING d.id, s.url, d.event_type, d.payload, d.attempts
+        "#,
+        DISPATCH_BATCH_SIZE
+    )
+    .fetch_all(pool)
+    .await?;
+
+    for delivery in due {
+        let body = serde_json::to_vec(&delivery.payload).expect("JSON values serialise");
+        let headers = delivery_headers(&delivery.event_type, delivery.id.to_string());
+        let outcome = http::post_json(&delivery.url, headers, body).await;
+
+        match delivery_error(&outcome) {
+            None => {
+                sqlx::query!(
+                    r#"
+                    UPDATE webhook_deliveries
+                    SET delivered_at = NOW(), next_attempt_at = NULL, last_error = NULL
+                    WHERE id = $1
+                    "#,
+                    delivery.id
+                )
+                .execute(pool)
+                .await?;
+            }
+            Some(error) => {
+                let next_attempt_at = (delivery.attempts < MAX_ATTEMPTS)
+                    .then(|| Utc::now() + retry_delay(delivery.attempts));
+                sqlx::query!(
+                    r#"
+                    UPDATE webhook_deliveries
+                    SET last_error = $2, next_attempt_at = $3
+                    WHERE id = $1
+                    "#,
+                    delivery.id,
+                    error,
+                    next_attempt_at
+                )
+                .execute(pool)
+                .await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn validate(payload: &SubscriptionRequest) -> Result<(), ApiError> {
+    if !(payload.url.starts_with("http://") || payload.url.starts_with("https://")) {
+        return Err(ApiError::BadRequest("url must be an http(s) URL".to_string()));
+    }
+    for filter in &payload.event_types {
+        let known = match filter.strip_suffix('*') {
+            Some(prefix) => prefix.ends_with('.') && EVENT_TYPES.iter().any(|e| e.starts_with(prefix)),
+            None => EVENT_TYPES.contains(&filter.as_str()),
+        };
+        if !known {
+            return Err(ApiError::BadRequest(format!(
+                "Unknown event type filter '{filter}', expected one of: {}",
+                EVENT_TYPES.join(", ")
+            )));
+        }
+    }
+    match (&payload.fields, &payload.template) {
+        (Some(_), Some(_)) => {
+            return Err(ApiError::BadRequest(
+                "fields and template cannot both be set".to_string(),
+            ))
+        }
+        (Some(fields), None) => {
+            if fields.is_empty() {
+                return Err(ApiError::BadRequest("fields must not be empty".to_string()));
+            }
+            if let Some(field) = fields.iter().find(|f| parse_path(f).is_none()) {
+                return Err(ApiError::BadRequest(format!("Invalid field path '{field}'")));
+            }
+        }
+        (None, Some(template)) => {
+            if let Some(path) = invalid_template_path(template) {
+                return Err(ApiError::BadRequest(format!(
+                    "Invalid template placeholder '{{{{{path}}}}}'"
+                )));
+            }
+        }
+        (None, None) => {}
+    }
+    Ok(())
+}
+
+async fn create_subscription(
+    _admin: Admin,
+    State(state): State<AppState>,
+    Json(payload): Json<SubscriptionRequest>,
+) -> Result<Json<Subscription>, ApiError> {
+    validate(&payload)?;
+
+    let subscription = sqlx::query_as!(
+        Subscription,
+        r#"
+        INSERT INTO webhook_subscriptions (url, event_types, fields, template)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id, url, event_types, fields, template, created_at
+        "#,
+        payload.url,
+        &payload.event_types,
+        payload.fields.as_deref(),
+        payload.template
+    )
+    .fetch_one(&state.pool)
+    .await
+    .map_err(|_| ApiError::InternalServerError)?;
+
+    Ok(Json(subscription))
+}
+
+async fn list_subscriptions(
+    _admin: Admin,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<Subscription>>, ApiError> {
+    let subscriptions = sqlx::query_as!(
+        Subscription,
+        r#"
+        SELECT id, url, event_types, fields, template, created_at
+        FROM webhook_subscriptions
+        ORDER BY created_at
+        "#
+    )
+    .fetch_all(&state.pool)
+    .await
+    .map_err(|_| ApiError::InternalServerError)?;
+
+    Ok(Json(subscriptions))
+}
+
+async fn find_subscription(state: &AppState, id: Uuid) -> Result<Subscription, ApiError> {
+    sqlx::query_as!(
+        Subscription,
+        r#"
+        SELECT id, url, event_types, fields, template, created_at
+        FROM webhook_subscriptions
+        WHERE id = $1
+        "#,
+        id
+    )
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(|_| ApiError::InternalServerError)?
+    .ok_or(ApiError::NotFound("Webhook subscription"))
+}
+
+async fn get_subscription(
+    _admin: Admin,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Subscription>, ApiError> {
+    Ok(Json(find_subscription(&state, id).await?))
+}
+
+async fn update_subscription(
+    _admin: Admin,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<SubscriptionRequest>,
+) -> Result<Json<Subscription>, ApiError> {
+    validate(&payload)?;
+
+    let subscription = sqlx::query_as!(
+        Subscription,
+        r#"
+        UPDATE webhook_subscriptions
+        SET url = $2, event_types = $3, fields = $4, template = $5, updated_at = NOW()
+        WHERE id = $1
+        RETURNING id, url, event_types, fields, template, created_at
+        "#,
+        id,
+        payload.url,
+        &payload.event_types,
+        payload.fields.as_deref(),
+        payload.template
+    )
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(|_| ApiError::InternalServerError)?
+    .ok_or(ApiError::NotFound("Webhook subscription"))?;
+
+    Ok(Json(subscription))
+}
+
+async fn delete_subscription(
+    _admin: Admin,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    let deleted = sqlx::query!("DELETE FROM webhook_subscriptions WHERE id = $1", id)
+        .execute(&state.pool)
+        .await
+        .map_err(|_| ApiError::InternalServerError)?
+        .rows_affected();
+
+    if deleted == 0 {
+        return Err(ApiError::NotFound("Webhook subscription"));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn sample_data(event_type: &str) -> Value {
+    match event_type {
+        STUDENT_CREATED => json!({ "username": "jdoe", "name": "Jane Doe", "matric_number": null }),
+        STUDENT_MATRIC_ASSIGNED => {
+            json!({ "username": "jdoe", "name": "Jane Doe", "matric_number": "MAT00042" })
+        }
+        MEETING_BOOKED => json!({
+            "id": Uuid::nil(),
+            "slot": {
+                "id": Uuid::nil(),
+                "teacher": "mr.bello",
+                "starts_at": "2030-01-10T09:00:00Z",
+                "ends_at": "2030-01-10T09:15:00Z",
+                "location": "Room 4",
+                "booked": true,
+            },
+            "student_name": "Jane Doe",
+            "matric_number": "MAT00042",
+            "guardian_name": "John Doe",
+            "guardian_email": "john.doe@example.com",
+            "reminder_at": "2030-01-09T09:00:00Z",
+        }),
+        _ => Value::Null,
+    }
+}
+
+/// The event type and sample payload shared by [`preview`] and
+/// [`test_delivery`]. The event type defaults to the first one the subscription
+/// listens to, and the data to a sample for that event type; the payload is
+/// `None` when the subscription does not take that event type.
+async fn sample_payload(
+    state: &AppState,
+    id: Uuid,
+    request: SampleRequest,
+) -> Result<(Subscription, String, Option<Value>), ApiError> {
+    let subscription = find_subscription(state, id).await?;
+
+    let event_type = match request.event_type {
+        Some(event_type) if EVENT_TYPES.contains(&event_type.as_str()) => event_type,
+        Some(event_type) => {
+            return Err(ApiError::BadRequest(format!("Unknown event type '{event_type}'")))
+        }
+        None => EVENT_TYPES
+            .iter()
+            .find(|e| subscription.matches(e))
+            .unwrap_or(&STUDENT_CREATED)
+            .to_string(),
+    };
+
+    if !subscription.matches(&event_type) {
+        return Ok((subscription, event_type, None));
+    }
+
+    let data = request.data.unwrap_or_else(|| sample_data(&event_type));
+    let payload = subscription.shape(&envelope(&event_type, data));
+    Ok((subscription, event_type, Some(payload)))
+}
+
+/// Shows the payload a subscription would receive for an event, without
+/// queueing or sending anything.
+async fn preview(
+    _admin: Admin,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    payload: Option<Json<SampleRequest>>,
+) -> Result<Json<PreviewResponse>, ApiError> {
+    let Json(request) = payload.unwrap_or_default();
+    let (_, event_type, payload) = sample_payload(&state, id, request).await?;
+
+    Ok(Json(PreviewResponse {
+        event_type,
+        matched: payload.is_some(),
+        payload,
+    }))
+}
+
+/// Posts a sample event to the subscription's `url` right away and reports
+/// how the subscriber answered. Nothing is queued, so a failed test is not
+/// retried. Nothing is sent if the subscription does not take the event type.
+async fn test_delivery(
+    _admin: Admin,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    payload: Option<Json<SampleRequest>>,
+) -> Result<Json<TestDeliveryResponse>, ApiError> {
+    let Json(request) = payload.unwrap_or_default();
+    let (subscription, event_type, payload) = sample_payload(&state, id, request).await?;
+
+    let Some(payload) = payload else {
+        return Ok(Json(TestDeliveryResponse {
+            event_type,
+            matched: false,
+            payload: None,
+            status: None,
+            error: None,
+        }));
+    };
+
+    let body = serde_json::to_vec(&payload).map_err(|_| ApiError::InternalServerError)?;
+    let headers = delivery_headers(&event_type, "test".to_string());
+    let outcome = http::post_json(&subscription.url, headers, body).await;
+
+    Ok(Json(TestDeliveryResponse {
+        event_type,
+        matched: true,
+        payload: Some(payload),
+        status: outcome.as_ref().ok().copied(),
+        error: delivery_error(&outcome),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event() -> Value {
+        json!({
+            "event": "meeting.booked",
+            "data": {
+                "name": "Jane Doe",
+                "count": 3,
+                "slot": { "teacher": "mr.bello", "booked": true },
+                "tags": ["a", "b"],
+            },
+        })
+    }
+
+    fn request(fields: Option<Vec<&str>>, template: Option<Value>) -> SubscriptionRequest {
+        SubscriptionRequest {
+            url: "https://example.com/hook".to_string(),
+            event_types: vec![],
+            fields: fields.map(|f| f.into_iter().map(String::from).collect()),
+            template,
+        }
+    }
+
+    #[test]
+    fn filters_match_exact_types_and_dotted_prefixes() {
+        assert!(filter_matches("student.created", "student.created"));
+        assert!(!filter_matches("student.created", "student.matric_assigned"));
+        assert!(filter_matches("student.*", "student.matric_assigned"));
+        assert!(!filter_matches("student.*", "meeting.booked"));
+        // A wildcard has to follow a dot, and never matches across one.
+        assert!(!filter_matches("stu*", "student.created"));
+        assert!(!filter_matches("*", "student.created"));
+    }
+
+    #[test]
+    fn paths_accept_dollar_dot_dollar_and_bare_forms() {
+        assert_eq!(parse_path("$.data.name"), Some(vec!["data", "name"]));
+        assert_eq!(parse_path("$data.name"), Some(vec!["data", "name"]));
+        assert_eq!(parse_path(" data.name "), Some(vec!["data", "name"]));
+        assert_eq!(parse_path("$"), None);
+        assert_eq!(parse_path("data..name"), None);
+        assert_eq!(parse_path("data."), None);
+        assert_eq!(parse_path(""), None);
+    }
+
+    #[test]
+    fn lookup_follows_keys_and_array_indices() {
+        let event = event();
+        assert_eq!(lookup(&event, &["data", "slot", "teacher"]), Some(&json!("mr.bello")));
+        assert_eq!(lookup(&event, &["data", "tags", "1"]), Some(&json!("b")));
+        assert_eq!(lookup(&event, &["data", "tags", "9"]), None);
+        assert_eq!(lookup(&event, &["data", "name", "first"]), None);
+    }
+
+    #[test]
+    fn select_fields_keeps_nesting() {
+        let fields = vec!["$.event".to_string(), "data.slot.teacher".to_string()];
+        assert_eq!(
+            select_fields(&event(), &fields),
+            json!({ "event": "meeting.booked", "data": { "slot": { "teacher": "mr.bello" } } })
+        );
+    }
+
+    #[test]
+    fn select_fields_skips_conflicting_and_missing_paths() {
+        // `data.name` is a string, so `data.name.first` cannot nest under it.
+        let fields = vec![
+            "data.name".to_string(),
+            "data.name.first".to_string(),
+            "data.missing".to_string(),
+        ];
+        assert_eq!(select_fields(&event(), &fields), json!({ "data": { "name": "Jane Doe" } }));
+
+        // A whole object selected first still accepts a nested field into it.
+        let fields = vec!["data.slot".to_string(), "data.count".to_string()];
+        assert_eq!(
+            select_fields(&event(), &fields),
+            json!({ "data": { "slot": { "teacher": "mr.bello", "booked": true }, "count": 3 } })
+        );
+    }
+
+    #[test]
+    fn lone_placeholder_keeps_json_type() {
+        let event = event();
+        assert_eq!(render_string("{{data.count}}", &event), json!(3));
+        assert_eq!(render_string("{{data.slot}}", &event), json!({ "teacher": "mr.bello", "booked": true }));
+        assert_eq!(render_string("{{$.data.slot.booked}}", &event), json!(true));
+        assert_eq!(render_string("{{data.missing}}", &event), Value::Null);
+    }
+
+    #[test]
+    fn mixed_text_substitutes_values_as_text() {
+        let event = event();
+        assert_eq!(
+            render_string("{{data.name}} has {{data.count}} with {{data.slot.teacher}}", &event),
+            json!("Jane Doe has 3 with mr.bello")
+        );
+        assert_eq!(render_string("x{{data.missing}}y", &event), json!("xy"));
+        assert_eq!(
+            render_template(&json!({ "a": ["{{data.count}}", "n={{data.count}}"], "b": 1 }), &event),
+            json!({ "a": [3, "n=3"], "b": 1 })
+        );
+    }
+
+    #[test]
+    fn unterminated_placeholder_is_kept_literally() {
+        let event = event();
+        assert_eq!(render_string("hi {{data.name", &event), json!("hi {{data.name"));
+        assert_eq!(
+            render_string("{{data.name}} and {{data.count", &event),
+            json!("Jane Doe and {{data.count")
+        );
+        assert!(placeholders("hi {{data.name").is_empty());
+    }
+
+    #[test]
+    fn validate_rejects_bad_field_and_template_paths() {
+        assert!(validate(&request(Some(vec!["data.name"]), None)).is_ok());
+        assert!(validate(&request(Some(vec!["data..name"]), None)).is_err());
+        assert!(validate(&request(Some(vec![]), None)).is_err());
+
+        assert!(validate(&request(None, Some(json!({ "x": "{{data.name}}" })))).is_ok());
+        assert!(validate(&request(None, Some(json!({ "x": ["ok", "{{data..name}}"] })))).is_err());
+        assert!(validate(&request(None, Some(json!("{{}}")))).is_err());
+        assert!(validate(&request(None, Some(json!("{{ unterminated")))).is_ok());
+
+        assert!(validate(&request(Some(vec!["data.name"]), Some(json!({})))).is_err());
+    }
+
+    #[test]
+    fn only_2xx_answers_count_as_delivered() {
+        assert_eq!(delivery_error(&Ok(200)), None);
+        assert_eq!(delivery_error(&Ok(204)), None);
+        assert_eq!(
+            delivery_error(&Ok(301)),
+            Some("subscriber answered with HTTP 301".to_string())
+        );
+        assert!(delivery_error(&Ok(500)).is_some());
+        let refused = Err(HttpError::Io(std::io::ErrorKind::ConnectionRefused.into()));
+        assert!(delivery_error(&refused).is_some_and(|e| e.starts_with("connection failed")));
+    }
+
+    #[test]
+    fn retries_back_off_exponentially_up_to_an_hour() {
+        assert_eq!(retry_delay(1), Duration::seconds(30));
+        assert_eq!(retry_delay(2), Duration::seconds(60));
+        assert_eq!(retry_delay(3), Duration::minutes(2));
+        assert_eq!(retry_delay(MAX_ATTEMPTS - 1), Duration::seconds(1920));
+        assert_eq!(retry_delay(MAX_ATTEMPTS), Duration::hours(1));
+        assert_eq!(retry_delay(0), Duration::seconds(30));
+        assert_eq!(retry_delay(i32::MAX), Duration::hours(1));
+    }
+
+    #[test]
+    fn deliveries_name_their_event_and_id() {
+        assert_eq!(
+            delivery_headers(MEETING_BOOKED, "test".to_string()),
+            vec![
+                ("X-Webhook-Event", "meeting.booked".to_string()),
+                ("X-Webhook-Delivery", "test".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_rejects_unknown_filters_and_urls() {
+        let mut payload = request(None, None);
+        payload.event_types = vec!["student.*".to_string(), "meeting.booked".to_string()];
+        assert!(validate(&payload).is_ok());
+
+        payload.event_types = vec!["grades.*".to_string()];
+        assert!(validate(&payload).is_err());
+
+        payload.event_types = vec![];
+        payload.url = "ftp://example.com".to_string();
+        assert!(validate(&payload).is_err());
+    }
+}